@@ -0,0 +1,210 @@
+use crate::{
+    bird::{BIRD_RADIUS, BIRD_SCALE, BirdMarker},
+    config::GameConfig,
+    input::{ButtonPressed, LiveInputSet},
+    obstacles::{Gap, ObstacleNotScored},
+    *,
+};
+use bevy_rapier2d::prelude::*;
+
+/// How long the menu sits idle before the autopilot starts a demo run.
+const ATTRACT_DELAY_SECONDS: f32 = 8.0;
+
+/// How far ahead, in seconds, the autopilot predicts the bird's fall.
+const LOOKAHEAD_SECONDS: f32 = 0.1;
+
+/// Extra room kept between the bird and the bottom pipe.
+const CLEARANCE: f32 = 12.0;
+
+pub struct AutopilotPlugin;
+
+impl Plugin for AutopilotPlugin {
+    fn build(&self, application: &mut App) {
+        application
+            .init_resource::<Autopilot>()
+            .insert_resource(AttractTimer(Timer::from_seconds(
+                ATTRACT_DELAY_SECONDS,
+                TimerMode::Once,
+            )))
+            .configure_sets(Update, LiveInputSet.run_if(not(in_attract_mode)))
+            .add_systems(
+                FixedPreUpdate,
+                fly_bird
+                    .run_if(autopilot_enabled)
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(in_state(GameState::Running)),
+            )
+            .add_systems(Update, toggle_autopilot.run_if(in_state(AppState::InGame)))
+            .add_systems(
+                OnEnter(AppState::Menu),
+                (end_attract_mode, reset_attract_timer),
+            )
+            .add_systems(Update, start_attract_mode.run_if(in_state(AppState::Menu)))
+            .add_systems(
+                Update,
+                leave_attract_mode
+                    .run_if(in_attract_mode)
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                return_to_menu.run_if(in_attract_mode),
+            );
+    }
+}
+
+/// Whether the bird is being flown by the built-in bot.
+#[derive(Debug, Default, Resource)]
+pub struct Autopilot {
+    pub enabled: bool,
+    /// Set while the bot is playing a demo run from the start menu.
+    pub attract: bool,
+}
+
+/// Everything the autopilot looks at to decide whether to flap.
+#[derive(Debug, Clone, Copy)]
+pub struct FlightState {
+    pub bird_y: f32,
+    pub velocity_y: f32,
+    /// Downward acceleration on the bird, as a negative number.
+    pub gravity_y: f32,
+    /// Bottom and top of the next gap the bird has to fly through, if any.
+    pub next_gap: Option<(f32, f32)>,
+}
+
+impl FlightState {
+    /// Flaps once the bird is about to drop too close to the bottom of the
+    /// next gap, or below the middle of the screen when there is none.
+    pub fn should_flap(&self) -> bool {
+        if self.velocity_y > 0.0 {
+            return false;
+        }
+
+        let floor = match self.next_gap {
+            Some((bottom, _)) => bottom + BIRD_RADIUS * BIRD_SCALE + CLEARANCE,
+            None => 0.0,
+        };
+        let predicted_y = self.bird_y
+            + self.velocity_y * LOOKAHEAD_SECONDS
+            + 0.5 * self.gravity_y * LOOKAHEAD_SECONDS * LOOKAHEAD_SECONDS;
+
+        predicted_y < floor
+    }
+}
+
+#[derive(Resource, Deref, DerefMut)]
+struct AttractTimer(Timer);
+
+fn autopilot_enabled(autopilot: Res<Autopilot>) -> bool {
+    autopilot.enabled
+}
+
+fn in_attract_mode(autopilot: Res<Autopilot>) -> bool {
+    autopilot.attract
+}
+
+/// Gathers the current [`FlightState`] of the bird from the world.
+pub fn flight_state(
+    bird: (&Transform, &Velocity, &GravityScale),
+    obstacles: impl Iterator<Item = (Transform, Gap)>,
+    gravity: Vec2,
+    config: &GameConfig,
+) -> FlightState {
+    let (bird_transform, bird_velocity, gravity_scale) = bird;
+    let bird_left = config.birb_x - BIRD_RADIUS * BIRD_SCALE;
+
+    let next_gap = obstacles
+        .filter(|(transform, _)| transform.translation.x + config.obstacle_width >= bird_left)
+        .min_by(|(a, _), (b, _)| a.translation.x.total_cmp(&b.translation.x))
+        .map(|(transform, gap)| gap.bounds(transform.translation.y));
+
+    FlightState {
+        bird_y: bird_transform.translation.y,
+        velocity_y: bird_velocity.linvel.y,
+        gravity_y: gravity.y * gravity_scale.0,
+        next_gap,
+    }
+}
+
+fn fly_bird(
+    bird: Query<(&Transform, &Velocity, &GravityScale), With<BirdMarker>>,
+    obstacles: Query<(&Transform, &Gap), ObstacleNotScored>,
+    physics_config: Query<&RapierConfiguration>,
+    config: Res<GameConfig>,
+    mut event_pressed: EventWriter<ButtonPressed>,
+) {
+    let Ok(bird) = bird.get_single() else {
+        return;
+    };
+    let gravity = physics_config
+        .iter()
+        .next()
+        .map(|physics_config| physics_config.gravity)
+        .unwrap_or_default();
+
+    let state = flight_state(
+        bird,
+        obstacles.iter().map(|(transform, gap)| (*transform, *gap)),
+        gravity,
+        &config,
+    );
+
+    if state.should_flap() {
+        event_pressed.send_default();
+    }
+}
+
+fn toggle_autopilot(keys: Res<ButtonInput<KeyCode>>, mut autopilot: ResMut<Autopilot>) {
+    if keys.just_pressed(KeyCode::KeyB) && !autopilot.attract {
+        autopilot.enabled = !autopilot.enabled;
+        info!("Autopilot {}", if autopilot.enabled { "on" } else { "off" });
+    }
+}
+
+fn reset_attract_timer(mut timer: ResMut<AttractTimer>) {
+    timer.reset();
+}
+
+fn end_attract_mode(mut autopilot: ResMut<Autopilot>) {
+    if autopilot.attract {
+        autopilot.attract = false;
+        autopilot.enabled = false;
+    }
+}
+
+fn start_attract_mode(
+    time: Res<Time>,
+    mut timer: ResMut<AttractTimer>,
+    mut input_pressed: EventReader<ButtonPressed>,
+    mut autopilot: ResMut<Autopilot>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if input_pressed.read().next().is_some() {
+        timer.reset();
+        return;
+    }
+
+    if timer.tick(time.delta()).just_finished() {
+        autopilot.enabled = true;
+        autopilot.attract = true;
+        next_state.set(AppState::InGame);
+    }
+}
+
+fn leave_attract_mode(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    touches: Res<Touches>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if keyboard.get_just_pressed().next().is_some()
+        || mouse.get_just_pressed().next().is_some()
+        || touches.any_just_pressed()
+    {
+        next_state.set(AppState::Menu);
+    }
+}
+
+fn return_to_menu(mut next_state: ResMut<NextState<AppState>>) {
+    next_state.set(AppState::Menu);
+}
@@ -0,0 +1,309 @@
+use crate::{
+    collision::bird_collision_groups,
+    config::GameConfig,
+    game::GameMode,
+    input::ButtonPressed,
+    multiplayer::{PLAYER_TINTS, PlayerCount},
+    physics::{InterpolatedVisual, PhysicsHistory},
+    *,
+};
+use bevy_rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub struct BirdPlugin;
+
+impl Plugin for BirdPlugin {
+    fn build(&self, application: &mut App) {
+        application
+            .add_event::<Flap>()
+            .add_systems(Startup, create_bird_atlas)
+            .add_systems(
+                OnEnter(AppState::InGame),
+                spawn_player_birds.run_if(not(resource_equals(GameMode::Training))),
+            )
+            .add_systems(
+                FixedUpdate,
+                ((forward_button_presses, flap_bird).chain(), animate_bird)
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(in_state(GameState::Running)),
+            )
+            .add_systems(
+                Update,
+                tilt_birds
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(in_state(GameState::Running)),
+            );
+    }
+}
+
+/// Radius of the bird's collider before it's scaled up with the sprite.
+pub const BIRD_RADIUS: f32 = 15.0;
+pub const BIRD_SCALE: f32 = 1.25;
+
+#[derive(Component)]
+pub struct BirdMarker;
+
+/// A bird flown by a person at the keyboard, rather than by a bot.
+#[derive(Debug, Clone, Copy, Component)]
+pub struct PlayerControlled {
+    /// Which player's button flaps it, counting from zero.
+    pub player: usize,
+}
+
+/// Asks a single bird to flap.
+#[derive(Debug, Clone, Copy, Event)]
+pub struct Flap {
+    pub bird: Entity,
+}
+
+/// The sprite sheet layout shared by every bird.
+#[derive(Resource)]
+pub struct BirdAtlas(pub Handle<TextureAtlasLayout>);
+
+#[derive(Component)]
+pub struct AnimationIndices {
+    pub first: usize,
+    pub last: usize,
+}
+
+#[derive(Component, Deref, DerefMut)]
+pub struct AnimationTimer(pub Timer);
+
+/// How a bird's sprite leans with its vertical speed, and how its wings
+/// speed up after a flap.
+///
+/// This is only for show: the body never rotates, so the collider stays the
+/// same whichever way the sprite points.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BirdTilt {
+    /// Degrees the bird noses up by while rising at a full flap's speed.
+    pub climb_angle: f32,
+    /// Degrees the bird noses down by once falling at `dive_velocity`.
+    pub dive_angle: f32,
+    pub dive_velocity: f32,
+    /// How quickly the sprite turns towards the angle its speed calls for.
+    /// Higher is snappier.
+    pub easing: f32,
+    /// How many times faster the wings beat straight after a flap, easing
+    /// back to normal as the bird stops rising.
+    pub flap_wing_speedup: f32,
+}
+
+impl Default for BirdTilt {
+    fn default() -> Self {
+        Self {
+            climb_angle: 25.0,
+            dive_angle: 90.0,
+            dive_velocity: 900.0,
+            easing: 12.0,
+            flap_wing_speedup: 3.0,
+        }
+    }
+}
+
+impl BirdTilt {
+    pub fn validate(&self) -> Result<(), String> {
+        for (field, angle) in [
+            ("climb_angle", self.climb_angle),
+            ("dive_angle", self.dive_angle),
+        ] {
+            if !(0.0..=90.0).contains(&angle) {
+                return Err(format!("{field} must be between 0 and 90, got {angle}"));
+            }
+        }
+
+        for (field, value) in [
+            ("dive_velocity", self.dive_velocity),
+            ("easing", self.easing),
+        ] {
+            if !value.is_finite() || value <= 0.0 {
+                return Err(format!("{field} must be greater than zero, got {value}"));
+            }
+        }
+
+        if !self.flap_wing_speedup.is_finite() || self.flap_wing_speedup < 1.0 {
+            return Err(format!(
+                "flap_wing_speedup must be at least 1, got {}",
+                self.flap_wing_speedup
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// The angle, in radians, a bird moving vertically at `velocity` should
+    /// be drawn at.
+    pub fn angle_for(&self, velocity: f32, jump_velocity: f32) -> f32 {
+        let degrees = if velocity >= 0.0 {
+            self.climb_angle * (velocity / jump_velocity).min(1.0)
+        } else {
+            -self.dive_angle * (-velocity / self.dive_velocity).min(1.0)
+        };
+
+        degrees.to_radians()
+    }
+}
+
+fn create_bird_atlas(
+    mut commands: Commands,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+) {
+    let layout = TextureAtlasLayout::from_grid(UVec2::new(34, 24), 4, 1, None, None);
+
+    commands.insert_resource(BirdAtlas(texture_atlas_layouts.add(layout)));
+}
+
+fn spawn_player_birds(
+    mut commands: Commands,
+    assets: Res<SpriteAssets>,
+    atlas: Res<BirdAtlas>,
+    config: Res<GameConfig>,
+    players: Res<PlayerCount>,
+) {
+    for (player, tint) in PLAYER_TINTS.into_iter().enumerate().take(players.0) {
+        let bird = spawn_bird(&mut commands, &assets, &atlas, &config, tint);
+
+        commands.entity(bird).insert(PlayerControlled { player });
+    }
+}
+
+/// Spawns a bird at the start position, returning the physics body.
+///
+/// The sprite lives on a child entity so it can be interpolated, tinted or
+/// rotated without touching the collider.
+pub fn spawn_bird(
+    commands: &mut Commands,
+    assets: &SpriteAssets,
+    atlas: &BirdAtlas,
+    config: &GameConfig,
+    tint: Color,
+) -> Entity {
+    let spawn_y = 128.0;
+
+    let birb_texture = assets.birb.clone();
+    let animation_indices = AnimationIndices { first: 0, last: 3 };
+    let mut sprite = Sprite::from_atlas_image(
+        birb_texture,
+        TextureAtlas {
+            layout: atlas.0.clone(),
+            index: animation_indices.first,
+        },
+    );
+    sprite.color = tint;
+
+    let transform = Transform::from_xyz(config.birb_x, spawn_y, 0.0)
+        .with_scale(Vec3::new(BIRD_SCALE, BIRD_SCALE, 0.0));
+
+    commands
+        .spawn((
+            Name::new("Birb"),
+            BirdMarker,
+            RigidBody::Dynamic,
+            Collider::ball(BIRD_RADIUS),
+            bird_collision_groups(),
+            ActiveEvents::all(),
+            transform,
+            PhysicsHistory::at(transform.translation),
+            GravityScale(config.gravity_scale),
+            Velocity::default(),
+            LockedAxes::ROTATION_LOCKED,
+            Visibility::Visible,
+            StateScoped(AppState::InGame),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Name::new("Birb sprite"),
+                sprite,
+                animation_indices,
+                AnimationTimer(Timer::from_seconds(0.2, TimerMode::Repeating)),
+                InterpolatedVisual,
+            ));
+        })
+        .id()
+}
+
+fn animate_bird(
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    bodies: Query<&Velocity>,
+    mut query: Query<(&Parent, &AnimationIndices, &mut AnimationTimer, &mut Sprite)>,
+) {
+    for (parent, animation_indices, mut animation_timer, mut sprite) in query.iter_mut() {
+        let rising = bodies.get(parent.get()).map_or(0.0, |velocity| {
+            (velocity.linvel.y / config.jump_velocity).clamp(0.0, 1.0)
+        });
+        let speed = 1.0 + (config.tilt.flap_wing_speedup - 1.0) * rising;
+
+        animation_timer.tick(time.delta().mul_f32(speed));
+
+        if animation_timer.just_finished()
+            && let Some(atlas) = &mut sprite.texture_atlas
+        {
+            atlas.index = if atlas.index == animation_indices.last {
+                animation_indices.first
+            } else {
+                atlas.index + 1
+            };
+        }
+    }
+}
+
+/// Eases each bird's sprite towards the angle its vertical speed calls for.
+fn tilt_birds(
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    birds: Query<(&Velocity, &Children)>,
+    mut sprites: Query<&mut Transform, With<AnimationIndices>>,
+) {
+    let blend = 1.0 - (-config.tilt.easing * time.delta_secs()).exp();
+
+    for (velocity, children) in birds.iter() {
+        let target = config
+            .tilt
+            .angle_for(velocity.linvel.y, config.jump_velocity);
+        let mut iter = sprites.iter_many_mut(children);
+
+        while let Some(mut transform) = iter.fetch_next() {
+            let (_, _, angle) = transform.rotation.to_euler(EulerRot::XYZ);
+            transform.rotation = Quat::from_rotation_z(angle + (target - angle) * blend);
+        }
+    }
+}
+
+fn forward_button_presses(
+    mut input_pressed: EventReader<ButtonPressed>,
+    players: Query<(Entity, &PlayerControlled), With<BirdMarker>>,
+    mut flaps: EventWriter<Flap>,
+) {
+    for pressed in input_pressed.read() {
+        flaps.send_batch(
+            players
+                .iter()
+                .filter(|(_, controlled)| controlled.player == pressed.player)
+                .map(|(bird, _)| Flap { bird }),
+        );
+    }
+}
+
+fn flap_bird(
+    mut birds: Query<&mut Velocity, With<BirdMarker>>,
+    mut flaps: EventReader<Flap>,
+    config: Res<GameConfig>,
+) {
+    for event in flaps.read() {
+        let Ok(mut bird_velocity) = birds.get_mut(event.bird) else {
+            continue;
+        };
+
+        flap(&mut bird_velocity, &config);
+    }
+}
+
+/// Launches a bird upwards, unless it's still rising quickly from the last
+/// flap.
+pub fn flap(velocity: &mut Velocity, config: &GameConfig) {
+    if velocity.linvel.y <= config.jump_velocity / 2.0 {
+        velocity.linvel.y = config.jump_velocity;
+    }
+}
@@ -0,0 +1,215 @@
+//! Command line options for the birb launcher.
+
+use crate::{date::Date, difficulty::Difficulty, game::GameMode, multiplayer::MAX_PLAYERS};
+use std::{fmt, path::PathBuf};
+
+pub const USAGE: &str = "\
+Usage: birb [options]
+       birb verify <replay> [--config <file> [--difficulty <name>]]
+
+Commands:
+  verify <replay>       Re-simulate a replay and check its claimed score,
+                        and with --config that it was played on that config
+                        at --difficulty (default normal)
+
+Options:
+  --seed <n>            Seed for the first run's pipe layout (default 42)
+  --random-seed         Pick a random seed instead
+  --mode <name>         Game mode: endless, daily or training
+  --date <yyyy-mm-dd>   Play this day's daily challenge instead of today's
+  --difficulty <name>   Difficulty: easy, normal or hard
+  --players <n>         Birds sharing the pipes, from 1 to 4 (default 1)
+  --headless            Run without a window, exiting when the bird dies
+  --frames <n>          Quit after this many frames
+  --config <file>       Use this game config instead of the bundled asset
+  --level <file>        Play a level file instead of endless pipes
+  --edit <file>         Open a level file in the editor, creating it if needed
+  --record <file>       Save the run's inputs to a replay file
+  --replay <file>       Play back a replay file
+  --autopilot           Let the built-in bot fly the bird
+  --population <n>      Birds per generation in training mode (default 50)
+  --champion <file>     Load and save the best trained network here
+  --gym                 Serve the gym protocol over stdin and stdout
+  --gym-port <port>     Serve the gym protocol on a local TCP port
+  --help                Show this message";
+
+const DEFAULT_SEED: u64 = 42;
+const DEFAULT_POPULATION: usize = 50;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cli {
+    pub seed: u64,
+    pub mode: GameMode,
+    pub date: Option<Date>,
+    pub difficulty: Option<Difficulty>,
+    pub players: usize,
+    pub headless: bool,
+    pub frames: Option<u32>,
+    pub config: Option<PathBuf>,
+    pub level: Option<PathBuf>,
+    pub edit: Option<PathBuf>,
+    pub record: Option<PathBuf>,
+    pub replay: Option<PathBuf>,
+    pub autopilot: bool,
+    pub population: usize,
+    pub champion: Option<PathBuf>,
+    pub verify: Option<PathBuf>,
+    pub gym: Option<GymTransport>,
+    pub help: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GymTransport {
+    Stdio,
+    Tcp(u16),
+}
+
+impl Default for Cli {
+    fn default() -> Self {
+        Self {
+            seed: DEFAULT_SEED,
+            mode: GameMode::default(),
+            date: None,
+            difficulty: None,
+            players: 1,
+            headless: false,
+            frames: None,
+            config: None,
+            level: None,
+            edit: None,
+            record: None,
+            replay: None,
+            autopilot: false,
+            population: DEFAULT_POPULATION,
+            champion: None,
+            verify: None,
+            gym: None,
+            help: false,
+        }
+    }
+}
+
+impl Cli {
+    pub fn from_env() -> Result<Self, CliError> {
+        Self::parse(std::env::args().skip(1))
+    }
+
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, CliError> {
+        let mut cli = Cli::default();
+        let mut args = args.into_iter().peekable();
+
+        if args.next_if(|arg| arg == "verify").is_some() {
+            let replay = args
+                .next()
+                .ok_or_else(|| CliError::MissingValue("verify".to_string()))?;
+            cli.verify = Some(replay.into());
+        }
+
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| CliError::MissingValue(arg.clone()))
+            };
+
+            match arg.as_str() {
+                "--seed" => cli.seed = parse_value(&arg, value()?)?,
+                "--random-seed" => cli.seed = random_seed(),
+                "--mode" => cli.mode = parse_value(&arg, value()?)?,
+                "--date" => cli.date = Some(parse_value(&arg, value()?)?),
+                "--difficulty" => cli.difficulty = Some(parse_value(&arg, value()?)?),
+                "--players" => {
+                    let players = value()?;
+                    cli.players = parse_value(&arg, players.clone())?;
+                    if !(1..=MAX_PLAYERS).contains(&cli.players) {
+                        return Err(CliError::InvalidValue {
+                            option: arg,
+                            value: players,
+                        });
+                    }
+                }
+                "--headless" => cli.headless = true,
+                "--frames" => cli.frames = Some(parse_value(&arg, value()?)?),
+                "--config" => cli.config = Some(value()?.into()),
+                "--level" => cli.level = Some(value()?.into()),
+                "--edit" => cli.edit = Some(value()?.into()),
+                "--record" => cli.record = Some(value()?.into()),
+                "--replay" => cli.replay = Some(value()?.into()),
+                "--autopilot" => cli.autopilot = true,
+                "--population" => cli.population = parse_value(&arg, value()?)?,
+                "--champion" => cli.champion = Some(value()?.into()),
+                "--gym" => cli.gym = Some(GymTransport::Stdio),
+                "--gym-port" => cli.gym = Some(GymTransport::Tcp(parse_value(&arg, value()?)?)),
+                "--help" | "-h" => cli.help = true,
+                _ => return Err(CliError::UnknownOption(arg)),
+            }
+        }
+
+        if cli.record.is_some() && cli.replay.is_some() {
+            return Err(CliError::Conflict("--record", "--replay"));
+        }
+
+        // Daily runs seed themselves from the date, not from the replay.
+        if cli.replay.is_some() && cli.mode == GameMode::Daily {
+            return Err(CliError::Conflict("--replay", "--mode daily"));
+        }
+
+        // Replays don't save the level, so they could only be played back
+        // on endless pipes.
+        if cli.record.is_some() && cli.level.is_some() {
+            return Err(CliError::Conflict("--record", "--level"));
+        }
+
+        if cli.edit.is_some() && cli.headless {
+            return Err(CliError::Conflict("--edit", "--headless"));
+        }
+
+        Ok(cli)
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(option: &str, value: String) -> Result<T, CliError> {
+    value.parse().map_err(|_| CliError::InvalidValue {
+        option: option.to_string(),
+        value,
+    })
+}
+
+/// A seed nobody chose, for sharing interesting runs after the fact.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn random_seed() -> u64 {
+    use std::hash::{BuildHasher, Hasher};
+
+    std::collections::hash_map::RandomState::new()
+        .build_hasher()
+        .finish()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn random_seed() -> u64 {
+    (js_sys::Math::random() * u32::MAX as f64) as u64
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CliError {
+    UnknownOption(String),
+    MissingValue(String),
+    InvalidValue { option: String, value: String },
+    Conflict(&'static str, &'static str),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::UnknownOption(option) => write!(f, "unknown option {option}"),
+            CliError::MissingValue(option) => write!(f, "{option} needs a value"),
+            CliError::InvalidValue { option, value } => {
+                write!(f, "{value:?} is not a valid value for {option}")
+            }
+            CliError::Conflict(first, second) => {
+                write!(f, "{first} can't be used together with {second}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
@@ -0,0 +1,165 @@
+//! Collision groups and typed hit events.
+//!
+//! Birds only ever collide with [`Hazard`]s, never with each other, and every
+//! contact is turned into one of [`BirdHitPipe`], [`BirdHitGround`] or
+//! [`BirdHitCeiling`] so gameplay can react to each kind on its own terms.
+
+use crate::{bird::BirdMarker, *};
+use bevy::ecs::system::SystemParam;
+use bevy_rapier2d::prelude::*;
+
+pub struct CollisionPlugin;
+
+impl Plugin for CollisionPlugin {
+    fn build(&self, application: &mut App) {
+        application
+            .add_event::<BirdHitPipe>()
+            .add_event::<BirdHitGround>()
+            .add_event::<BirdHitCeiling>()
+            .init_resource::<CeilingRule>()
+            .init_resource::<DeathCauses>()
+            .add_systems(
+                FixedUpdate,
+                sort_collisions
+                    .in_set(CollisionSet)
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(in_state(GameState::Running)),
+            );
+    }
+}
+
+pub const BIRD_GROUP: Group = Group::GROUP_1;
+pub const PIPE_GROUP: Group = Group::GROUP_2;
+pub const GROUND_GROUP: Group = Group::GROUP_3;
+pub const CEILING_GROUP: Group = Group::GROUP_4;
+
+/// Runs after Rapier's collision events have been sorted into hit events.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemSet)]
+pub struct CollisionSet;
+
+/// Something a bird can fly into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub enum Hazard {
+    Pipe,
+    Ground,
+    Ceiling,
+}
+
+impl Hazard {
+    /// Collision groups for a collider of this kind, which only touches birds.
+    pub fn collision_groups(self) -> CollisionGroups {
+        let membership = match self {
+            Hazard::Pipe => PIPE_GROUP,
+            Hazard::Ground => GROUND_GROUP,
+            Hazard::Ceiling => CEILING_GROUP,
+        };
+
+        CollisionGroups::new(membership, BIRD_GROUP)
+    }
+}
+
+/// Collision groups for a bird, which touches every hazard but no other bird.
+pub fn bird_collision_groups() -> CollisionGroups {
+    CollisionGroups::new(BIRD_GROUP, PIPE_GROUP | GROUND_GROUP | CEILING_GROUP)
+}
+
+#[derive(Debug, Clone, Copy, Event)]
+pub struct BirdHitPipe {
+    pub bird: Entity,
+}
+
+#[derive(Debug, Clone, Copy, Event)]
+pub struct BirdHitGround {
+    pub bird: Entity,
+}
+
+#[derive(Debug, Clone, Copy, Event)]
+pub struct BirdHitCeiling {
+    pub bird: Entity,
+}
+
+/// Reads all three hit events, for systems that handle every hazard in one
+/// place.
+#[derive(SystemParam)]
+pub struct BirdHits<'w, 's> {
+    pipe: EventReader<'w, 's, BirdHitPipe>,
+    ground: EventReader<'w, 's, BirdHitGround>,
+    ceiling: EventReader<'w, 's, BirdHitCeiling>,
+}
+
+impl BirdHits<'_, '_> {
+    /// Every unread hit, as the bird and what it flew into.
+    pub fn read(&mut self) -> impl Iterator<Item = (Entity, Hazard)> + '_ {
+        self.pipe
+            .read()
+            .map(|hit| (hit.bird, Hazard::Pipe))
+            .chain(self.ground.read().map(|hit| (hit.bird, Hazard::Ground)))
+            .chain(self.ceiling.read().map(|hit| (hit.bird, Hazard::Ceiling)))
+    }
+}
+
+/// Whether flying into the ceiling ends the run or just stops the bird.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Resource)]
+pub enum CeilingRule {
+    #[default]
+    Deadly,
+    Soft,
+}
+
+/// How many runs each kind of hazard has ended this session.
+#[derive(Debug, Default, Clone, Copy, Resource)]
+pub struct DeathCauses {
+    pub pipe: u32,
+    pub ground: u32,
+    pub ceiling: u32,
+}
+
+impl DeathCauses {
+    pub fn record(&mut self, hazard: Hazard) {
+        match hazard {
+            Hazard::Pipe => self.pipe += 1,
+            Hazard::Ground => self.ground += 1,
+            Hazard::Ceiling => self.ceiling += 1,
+        }
+    }
+
+    pub fn total(&self) -> u32 {
+        self.pipe + self.ground + self.ceiling
+    }
+}
+
+fn sort_collisions(
+    mut collision_events: EventReader<CollisionEvent>,
+    birds: Query<(), With<BirdMarker>>,
+    hazards: Query<&Hazard>,
+    mut pipe_hits: EventWriter<BirdHitPipe>,
+    mut ground_hits: EventWriter<BirdHitGround>,
+    mut ceiling_hits: EventWriter<BirdHitCeiling>,
+) {
+    for collision in collision_events.read() {
+        let CollisionEvent::Started(first, second, _) = *collision else {
+            continue;
+        };
+
+        let (bird, other) = if birds.contains(first) {
+            (first, second)
+        } else if birds.contains(second) {
+            (second, first)
+        } else {
+            continue;
+        };
+
+        match hazards.get(other) {
+            Ok(Hazard::Pipe) => {
+                pipe_hits.send(BirdHitPipe { bird });
+            }
+            Ok(Hazard::Ground) => {
+                ground_hits.send(BirdHitGround { bird });
+            }
+            Ok(Hazard::Ceiling) => {
+                ceiling_hits.send(BirdHitCeiling { bird });
+            }
+            Err(_) => {}
+        }
+    }
+}
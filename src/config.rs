@@ -0,0 +1,217 @@
+use crate::{
+    bird::{BirdMarker, BirdTilt},
+    difficulty::DifficultyCurves,
+    *,
+};
+use bevy::asset::{AssetLoader, LoadContext, io::Reader};
+use bevy_rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{fmt, path::Path};
+
+pub struct GameConfigPlugin;
+
+impl Plugin for GameConfigPlugin {
+    fn build(&self, application: &mut App) {
+        application
+            .init_asset::<GameConfig>()
+            .register_asset_loader(GameConfigLoader)
+            .init_resource::<GameConfig>()
+            .add_systems(
+                Update,
+                (
+                    apply_config_asset.run_if(not(resource_exists::<PinnedConfig>)),
+                    apply_live_tuning.run_if(resource_changed::<GameConfig>),
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(AssetCollection, Resource)]
+pub struct ConfigAssets {
+    #[asset(path = "config/game.config.ron")]
+    pub game: Handle<GameConfig>,
+}
+
+/// Gameplay tuning, loaded from `assets/config/game.config.ron`.
+///
+/// The active values live in the `GameConfig` resource; edits to the asset
+/// are validated and copied over it while the game runs.
+#[derive(Asset, TypePath, Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameConfig {
+    pub birb_x: f32,
+    pub jump_velocity: f32,
+    pub gravity_scale: f32,
+    pub time_between_spawn: f32,
+    pub pipe_speed: f32,
+    pub obstacle_width: f32,
+    pub gap_height_min: f32,
+    pub gap_height_max: f32,
+    /// Curves for each difficulty preset; see [`crate::difficulty`].
+    #[serde(default)]
+    pub difficulty: DifficultyCurves,
+    /// How the bird's sprite leans with its speed; see [`BirdTilt`].
+    #[serde(default)]
+    pub tilt: BirdTilt,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            birb_x: 40.0,
+            jump_velocity: 600.0,
+            gravity_scale: 1.4,
+            time_between_spawn: 2.0,
+            pipe_speed: 200.0,
+            obstacle_width: 20.0,
+            gap_height_min: 100.0,
+            gap_height_max: 400.0,
+            difficulty: DifficultyCurves::default(),
+            tilt: BirdTilt::default(),
+        }
+    }
+}
+
+impl GameConfig {
+    pub fn from_ron(contents: &str) -> Result<Self, GameConfigError> {
+        let config: GameConfig = ron::from_str(contents).map_err(GameConfigError::Parse)?;
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, GameConfigError> {
+        let contents = std::fs::read_to_string(path).map_err(GameConfigError::Io)?;
+
+        Self::from_ron(&contents)
+    }
+
+    pub fn validate(&self) -> Result<(), GameConfigError> {
+        let positive = [
+            ("jump_velocity", self.jump_velocity),
+            ("gravity_scale", self.gravity_scale),
+            ("time_between_spawn", self.time_between_spawn),
+            ("pipe_speed", self.pipe_speed),
+            ("obstacle_width", self.obstacle_width),
+        ];
+
+        for (field, value) in positive {
+            if !value.is_finite() || value <= 0.0 {
+                return Err(GameConfigError::Invalid(format!(
+                    "{field} must be greater than zero, got {value}"
+                )));
+            }
+        }
+
+        if !self.gap_height_min.is_finite()
+            || !self.gap_height_max.is_finite()
+            || self.gap_height_min >= self.gap_height_max
+        {
+            return Err(GameConfigError::Invalid(format!(
+                "gap_height_min ({}) must be less than gap_height_max ({})",
+                self.gap_height_min, self.gap_height_max
+            )));
+        }
+
+        self.difficulty
+            .validate()
+            .map_err(|reason| GameConfigError::Invalid(format!("difficulty.{reason}")))?;
+
+        self.tilt
+            .validate()
+            .map_err(|reason| GameConfigError::Invalid(format!("tilt.{reason}")))
+    }
+}
+
+#[derive(Debug)]
+pub enum GameConfigError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+    Invalid(String),
+}
+
+impl fmt::Display for GameConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameConfigError::Io(error) => write!(f, "could not read game config: {error}"),
+            GameConfigError::Parse(error) => write!(f, "invalid game config: {error}"),
+            GameConfigError::Invalid(reason) => write!(f, "invalid game config: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for GameConfigError {}
+
+impl From<std::io::Error> for GameConfigError {
+    fn from(error: std::io::Error) -> Self {
+        GameConfigError::Io(error)
+    }
+}
+
+/// Keeps the active [`GameConfig`] from being replaced by the config asset,
+/// for runs (like replays) that must use the exact values they were given.
+#[derive(Debug, Default, Resource)]
+pub struct PinnedConfig;
+
+#[derive(Default)]
+struct GameConfigLoader;
+
+impl AssetLoader for GameConfigLoader {
+    type Asset = GameConfig;
+    type Settings = ();
+    type Error = GameConfigError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        let contents = String::from_utf8_lossy(&bytes);
+        GameConfig::from_ron(&contents)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["config.ron"]
+    }
+}
+
+fn apply_config_asset(
+    mut asset_events: EventReader<AssetEvent<GameConfig>>,
+    assets: Res<Assets<GameConfig>>,
+    config_assets: Option<Res<ConfigAssets>>,
+    mut config: ResMut<GameConfig>,
+) {
+    let Some(config_assets) = config_assets else {
+        return;
+    };
+
+    for event in asset_events.read() {
+        if !event.is_loaded_with_dependencies(&config_assets.game)
+            && !event.is_modified(&config_assets.game)
+        {
+            continue;
+        }
+
+        if let Some(loaded) = assets.get(&config_assets.game)
+            && *config != *loaded
+        {
+            info!("Applying updated game config");
+            *config = loaded.clone();
+        }
+    }
+}
+
+/// Pipe speed is retuned along with the difficulty curve, in
+/// [`crate::difficulty`].
+fn apply_live_tuning(
+    config: Res<GameConfig>,
+    mut birds: Query<&mut GravityScale, With<BirdMarker>>,
+) {
+    for mut gravity_scale in birds.iter_mut() {
+        gravity_scale.0 = config.gravity_scale;
+    }
+}
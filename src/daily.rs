@@ -0,0 +1,239 @@
+//! The daily challenge: one pipe layout per day, the same for everyone.
+//!
+//! The seed is derived from the date alone, and daily runs are always played
+//! on [`Difficulty::Normal`], so two players on the same day race through the
+//! same pipes. Each day's best score is kept apart from the endless high
+//! scores.
+
+use crate::{
+    autopilot::Autopilot,
+    date::Date,
+    difficulty::Difficulty,
+    game::{GameMode, PendingSeed, Score},
+    levels::ActiveLevel,
+    multiplayer::single_player,
+    *,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Save format of [`DailyBests`]; see [`storage`].
+pub const DAILY_BESTS_VERSION: u32 = 1;
+
+const STORAGE_KEY: &str = "daily";
+
+pub struct DailyPlugin;
+
+impl Plugin for DailyPlugin {
+    fn build(&self, application: &mut App) {
+        application
+            .init_resource::<DailyChallenge>()
+            .init_resource::<ChosenDifficulty>()
+            .add_systems(OnEnter(AppState::Menu), spawn_daily_panel)
+            .add_systems(
+                Update,
+                (toggle_daily, update_daily_panel)
+                    .chain()
+                    .run_if(in_state(AppState::Menu)),
+            )
+            .add_systems(
+                OnEnter(AppState::InGame),
+                use_daily_seed
+                    .before(obstacles::seed_rng)
+                    .run_if(resource_equals(GameMode::Daily)),
+            )
+            .add_systems(OnExit(AppState::InGame), restore_chosen_difficulty);
+    }
+}
+
+/// Keeps each day's best score. Left out alongside the high scores when runs
+/// are simulated or replayed.
+pub struct DailyBestsPlugin;
+
+impl Plugin for DailyBestsPlugin {
+    fn build(&self, application: &mut App) {
+        application.insert_resource(DailyBests::load()).add_systems(
+            OnEnter(GameState::GameOver),
+            record_daily_best
+                .run_if(resource_equals(GameMode::Daily))
+                .run_if(not(resource_exists::<ActiveLevel>))
+                .run_if(single_player),
+        );
+    }
+}
+
+/// The day being played, and the seed its pipes come from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Resource)]
+pub struct DailyChallenge {
+    pub date: Date,
+    pub seed: u64,
+}
+
+impl DailyChallenge {
+    pub fn on(date: Date) -> Self {
+        Self {
+            date,
+            seed: daily_seed(date),
+        }
+    }
+}
+
+impl Default for DailyChallenge {
+    fn default() -> Self {
+        Self::on(Date::today())
+    }
+}
+
+/// The seed for `date`'s challenge.
+///
+/// The date is packed into a number like 20250131 and run through
+/// SplitMix64, so neighbouring days get unrelated layouts.
+pub fn daily_seed(date: Date) -> u64 {
+    let packed = date.year as u64 * 10_000 + u64::from(date.month) * 100 + u64::from(date.day);
+
+    let mut z = packed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// The best score for every day played, persisted between sessions.
+#[derive(Debug, Clone, Resource, Serialize, Deserialize)]
+pub struct DailyBests {
+    pub version: u32,
+    pub days: BTreeMap<Date, usize>,
+}
+
+impl Default for DailyBests {
+    fn default() -> Self {
+        Self {
+            version: DAILY_BESTS_VERSION,
+            days: BTreeMap::new(),
+        }
+    }
+}
+
+impl DailyBests {
+    /// Reads the saved bests, starting afresh if there aren't readable ones.
+    pub fn load() -> Self {
+        storage::load_ron(STORAGE_KEY, DAILY_BESTS_VERSION).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        storage::save_ron(STORAGE_KEY, self);
+    }
+
+    pub fn get(&self, date: Date) -> Option<usize> {
+        self.days.get(&date).copied()
+    }
+
+    /// Records a run on `date`, returning whether it beat that day's best.
+    pub fn submit(&mut self, date: Date, score: usize) -> bool {
+        let best = self.days.entry(date).or_default();
+        let new_best = score > *best;
+        *best = (*best).max(score);
+
+        new_best
+    }
+}
+
+/// The difficulty the player picked, put aside while a daily run overrides
+/// it.
+#[derive(Debug, Default, Resource)]
+struct ChosenDifficulty(Option<Difficulty>);
+
+fn use_daily_seed(
+    daily: Res<DailyChallenge>,
+    mut pending_seed: ResMut<PendingSeed>,
+    mut difficulty: ResMut<Difficulty>,
+    mut chosen: ResMut<ChosenDifficulty>,
+) {
+    pending_seed.0 = Some(daily.seed);
+
+    if *difficulty != Difficulty::Normal {
+        chosen.0 = Some(*difficulty);
+        *difficulty = Difficulty::Normal;
+    }
+}
+
+fn restore_chosen_difficulty(
+    mut difficulty: ResMut<Difficulty>,
+    mut chosen: ResMut<ChosenDifficulty>,
+) {
+    if let Some(chosen) = chosen.0.take() {
+        *difficulty = chosen;
+    }
+}
+
+#[derive(Debug, Component)]
+pub struct DailyPanelMarker;
+
+fn spawn_daily_panel(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Daily challenge panel"),
+        DailyPanelMarker,
+        Text::default(),
+        TextFont::from_font_size(18.0),
+        TextLayout::new_with_justify(JustifyText::Right),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(65.0),
+            right: Val::Px(15.0),
+            ..default()
+        },
+        StateScoped(AppState::Menu),
+    ));
+}
+
+fn toggle_daily(keys: Res<ButtonInput<KeyCode>>, mut mode: ResMut<GameMode>) {
+    if !keys.just_pressed(KeyCode::KeyD) {
+        return;
+    }
+
+    match *mode {
+        GameMode::Endless => *mode = GameMode::Daily,
+        GameMode::Daily => *mode = GameMode::Endless,
+        GameMode::Training => {}
+    }
+}
+
+fn update_daily_panel(
+    mode: Res<GameMode>,
+    daily: Res<DailyChallenge>,
+    bests: Option<Res<DailyBests>>,
+    mut panel: Query<&mut Text, With<DailyPanelMarker>>,
+) {
+    let Ok(mut panel) = panel.get_single_mut() else {
+        return;
+    };
+
+    panel.0 = match *mode {
+        GameMode::Daily => {
+            let best = bests
+                .and_then(|bests| bests.get(daily.date))
+                .map_or("no runs yet".to_string(), |best| format!("best {best}"));
+            format!(
+                "Daily challenge {} (Normal)\n{best}\nD: Endless",
+                daily.date
+            )
+        }
+        GameMode::Endless => "D: Daily challenge".to_string(),
+        GameMode::Training => String::new(),
+    };
+}
+
+fn record_daily_best(
+    daily: Res<DailyChallenge>,
+    score: Res<Score>,
+    mut bests: ResMut<DailyBests>,
+    autopilot: Option<Res<Autopilot>>,
+) {
+    let flown_by_bot = autopilot.is_some_and(|autopilot| autopilot.enabled);
+    if score.value() == 0 || flown_by_bot {
+        return;
+    }
+
+    if bests.submit(daily.date, score.value()) {
+        bests.save();
+    }
+}
@@ -0,0 +1,163 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+/// A calendar date in UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn today() -> Self {
+        let seconds = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+
+        Self::from_days_since_epoch((seconds / 86_400) as i64)
+    }
+
+    // `SystemTime::now` panics on the web, so ask the browser instead.
+    #[cfg(target_arch = "wasm32")]
+    pub fn today() -> Self {
+        let now = js_sys::Date::new_0();
+
+        Self {
+            year: now.get_utc_full_year() as i32,
+            month: now.get_utc_month() + 1,
+            day: now.get_utc_date(),
+        }
+    }
+
+    /// Converts a day count since 1970-01-01 into a date, using Howard
+    /// Hinnant's `civil_from_days` algorithm.
+    pub fn from_days_since_epoch(days: i64) -> Self {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+        Self {
+            year: year as i32,
+            month: month as u32,
+            day: day as u32,
+        }
+    }
+
+    pub fn is_leap_year(year: i32) -> bool {
+        (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+    }
+
+    /// The number of days in `month` of `year`, or zero for a month that
+    /// doesn't exist.
+    pub fn days_in_month(year: i32, month: u32) -> u32 {
+        match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if Self::is_leap_year(year) => 29,
+            2 => 28,
+            _ => 0,
+        }
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl FromStr for Date {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("expected a date like 2025-01-31, got {value:?}");
+        let mut parts = value.splitn(3, '-');
+        let mut next = || parts.next().ok_or_else(invalid);
+
+        let year = next()?.parse().map_err(|_| invalid())?;
+        let month = next()?.parse().map_err(|_| invalid())?;
+        let day = next()?.parse().map_err(|_| invalid())?;
+
+        if !(1..=Self::days_in_month(year, month)).contains(&day) {
+            return Err(invalid());
+        }
+
+        Ok(Self { year, month, day })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> Date {
+        Date { year, month, day }
+    }
+
+    #[test]
+    fn converts_known_days_since_epoch() {
+        assert_eq!(Date::from_days_since_epoch(0), date(1970, 1, 1));
+        assert_eq!(Date::from_days_since_epoch(-1), date(1969, 12, 31));
+        assert_eq!(Date::from_days_since_epoch(11_016), date(2000, 2, 29));
+        assert_eq!(Date::from_days_since_epoch(19_782), date(2024, 2, 29));
+        assert_eq!(Date::from_days_since_epoch(20_818), date(2026, 12, 31));
+        assert_eq!(Date::from_days_since_epoch(47_541), date(2100, 3, 1));
+    }
+
+    #[test]
+    fn consecutive_days_step_through_the_calendar() {
+        let mut previous = Date::from_days_since_epoch(-40_000);
+
+        for days in -39_999..60_000 {
+            let current = Date::from_days_since_epoch(days);
+
+            let expected = if previous.day < Date::days_in_month(previous.year, previous.month) {
+                date(previous.year, previous.month, previous.day + 1)
+            } else if previous.month < 12 {
+                date(previous.year, previous.month + 1, 1)
+            } else {
+                date(previous.year + 1, 1, 1)
+            };
+            assert_eq!(current, expected, "{days} days since the epoch");
+
+            previous = current;
+        }
+    }
+
+    #[test]
+    fn parses_what_it_displays() {
+        for days in (-40_000..60_000).step_by(7) {
+            let date = Date::from_days_since_epoch(days);
+
+            assert_eq!(date.to_string().parse::<Date>(), Ok(date));
+        }
+    }
+
+    #[test]
+    fn rejects_days_past_the_end_of_the_month() {
+        assert_eq!("2026-02-28".parse::<Date>(), Ok(date(2026, 2, 28)));
+        assert!("2026-02-29".parse::<Date>().is_err());
+        assert!("2026-02-31".parse::<Date>().is_err());
+        assert!("2026-04-31".parse::<Date>().is_err());
+        assert_eq!("2024-02-29".parse::<Date>(), Ok(date(2024, 2, 29)));
+        assert_eq!("2000-02-29".parse::<Date>(), Ok(date(2000, 2, 29)));
+        assert!("1900-02-29".parse::<Date>().is_err());
+        assert!("2026-13-01".parse::<Date>().is_err());
+        assert!("2026-00-10".parse::<Date>().is_err());
+        assert!("2026-01-00".parse::<Date>().is_err());
+    }
+}
@@ -0,0 +1,168 @@
+//! What happens between a fatal hit and the game over screen.
+//!
+//! A hit on the last bird moves the game into [`GameState::Dying`] rather
+//! than straight to game over: the screen flashes, the pipes stop where they
+//! are and the bird tumbles nose first to the ground to the die sound. The
+//! run is only over once it lands. Nothing is simulated for scoring in the
+//! meantime, so [`crate::game::RunTick`] still reads the tick of the hit.
+
+use crate::{
+    bird::{BIRD_RADIUS, BIRD_SCALE, BirdMarker},
+    collision::{BIRD_GROUP, GROUND_GROUP},
+    game::{GROUND_HALF_HEIGHT, Playfield},
+    physics::InterpolatedVisual,
+    *,
+};
+use bevy::audio::Volume;
+use bevy_rapier2d::prelude::*;
+use std::f32::consts::FRAC_PI_2;
+
+pub struct DeathPlugin;
+
+impl Plugin for DeathPlugin {
+    fn build(&self, application: &mut App) {
+        application
+            .init_resource::<DyingTimer>()
+            .add_systems(
+                OnEnter(GameState::Dying),
+                (start_dying, drop_birds, stop_obstacles, spawn_screen_flash),
+            )
+            .add_systems(
+                FixedUpdate,
+                finish_dying
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(in_state(GameState::Dying)),
+            )
+            .add_systems(
+                Update,
+                (
+                    tumble_birds.run_if(in_state(GameState::Dying)),
+                    fade_screen_flash,
+                )
+                    .run_if(in_state(AppState::InGame)),
+            );
+    }
+}
+
+/// The shortest a death lasts, so a bird that died on the ground still gets
+/// a moment before the results cover it.
+const MIN_DYING_SECONDS: f32 = 0.5;
+/// The longest a death lasts, in case a bird never settles on the ground.
+const MAX_DYING_SECONDS: f32 = 3.0;
+/// How close to resting on the ground a bird has to be to count as landed.
+const LANDING_TOLERANCE: f32 = 2.0;
+
+/// How quickly a falling bird turns nose down, in radians per second.
+const TUMBLE_SPEED: f32 = 10.0;
+
+const FLASH_SECONDS: f32 = 0.3;
+const FLASH_ALPHA: f32 = 0.8;
+
+#[derive(Debug, Resource)]
+struct DyingTimer(Timer);
+
+impl Default for DyingTimer {
+    fn default() -> Self {
+        Self(Timer::from_seconds(MAX_DYING_SECONDS, TimerMode::Once))
+    }
+}
+
+fn start_dying(mut commands: Commands, mut timer: ResMut<DyingTimer>, audio: Res<AudioAssets>) {
+    timer.0.reset();
+
+    commands.spawn((
+        Name::new("Die audio"),
+        AudioPlayer::new(audio.die.clone()),
+        PlaybackSettings::DESPAWN.with_volume(Volume::new(0.3)),
+    ));
+}
+
+/// Lets the birds fall straight down through everything but the ground.
+fn drop_birds(mut birds: Query<(&mut Velocity, &mut CollisionGroups), With<BirdMarker>>) {
+    for (mut velocity, mut groups) in birds.iter_mut() {
+        velocity.linvel.x = 0.0;
+        velocity.linvel.y = velocity.linvel.y.min(0.0);
+        *groups = CollisionGroups::new(BIRD_GROUP, GROUND_GROUP);
+    }
+}
+
+fn stop_obstacles(mut bodies: Query<(&RigidBody, &mut Velocity)>) {
+    for (body, mut velocity) in bodies.iter_mut() {
+        if *body == RigidBody::KinematicVelocityBased {
+            *velocity = Velocity::zero();
+        }
+    }
+}
+
+fn tumble_birds(
+    time: Res<Time>,
+    birds: Query<&Children, With<BirdMarker>>,
+    mut sprites: Query<&mut Transform, With<InterpolatedVisual>>,
+) {
+    for children in birds.iter() {
+        let mut iter = sprites.iter_many_mut(children);
+
+        while let Some(mut transform) = iter.fetch_next() {
+            let (_, _, angle) = transform.rotation.to_euler(EulerRot::XYZ);
+            let angle = (angle - TUMBLE_SPEED * time.delta_secs()).max(-FRAC_PI_2);
+
+            transform.rotation = Quat::from_rotation_z(angle);
+        }
+    }
+}
+
+fn finish_dying(
+    time: Res<Time>,
+    mut timer: ResMut<DyingTimer>,
+    birds: Query<&Transform, With<BirdMarker>>,
+    playfield: Res<Playfield>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    timer.0.tick(time.delta());
+
+    let resting_y = -playfield.height / 2.0 + GROUND_HALF_HEIGHT + BIRD_RADIUS * BIRD_SCALE;
+    let landed = birds
+        .iter()
+        .all(|transform| transform.translation.y <= resting_y + LANDING_TOLERANCE);
+
+    if (landed && timer.0.elapsed_secs() >= MIN_DYING_SECONDS) || timer.0.finished() {
+        next_state.set(GameState::GameOver);
+    }
+}
+
+#[derive(Debug, Component)]
+pub struct ScreenFlash(Timer);
+
+fn spawn_screen_flash(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Screen flash"),
+        ScreenFlash(Timer::from_seconds(FLASH_SECONDS, TimerMode::Once)),
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(1.0, 1.0, 1.0, FLASH_ALPHA)),
+        StateScoped(AppState::InGame),
+    ));
+}
+
+fn fade_screen_flash(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut flashes: Query<(Entity, &mut ScreenFlash, &mut BackgroundColor)>,
+) {
+    for (entity, mut flash, mut background) in flashes.iter_mut() {
+        flash.0.tick(time.delta());
+
+        if flash.0.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        background
+            .0
+            .set_alpha(FLASH_ALPHA * flash.0.fraction_remaining());
+    }
+}
@@ -0,0 +1,324 @@
+//! How the pipes get harder as the score rises.
+//!
+//! Each [`Difficulty`] preset picks one of the [`DifficultyCurves`] from the
+//! game config, and the curve turns the current score into the [`Pacing`]
+//! new pipes are spawned with.
+
+use crate::{
+    config::GameConfig,
+    game::Score,
+    levels::ActiveLevel,
+    obstacles::{ObstacleMarker, ObstacleSpawnTimer},
+    *,
+};
+use bevy::ecs::system::SystemParam;
+use bevy_rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr, time::Duration};
+
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, application: &mut App) {
+        application
+            .init_resource::<Difficulty>()
+            .add_systems(OnEnter(AppState::Menu), spawn_difficulty_selector)
+            .add_systems(
+                Update,
+                (select_difficulty, update_difficulty_selector)
+                    .chain()
+                    .run_if(in_state(AppState::Menu)),
+            )
+            .add_systems(
+                FixedUpdate,
+                apply_pacing
+                    // Passing a pipe speeds up the rest on the same tick,
+                    // before the next one is due.
+                    .after(game::player_scored)
+                    .before(obstacles::tick_spawn_timer)
+                    .run_if(
+                        resource_changed::<Score>
+                            .or(resource_changed::<GameConfig>)
+                            .or(resource_changed::<Difficulty>),
+                    )
+                    // Levels set their own pace.
+                    .run_if(not(resource_exists::<ActiveLevel>))
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(in_state(GameState::Running)),
+            );
+    }
+}
+
+/// The difficulty preset runs are played on.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Resource, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    pub fn curve(self, config: &GameConfig) -> &DifficultyCurve {
+        match self {
+            Difficulty::Easy => &config.difficulty.easy,
+            Difficulty::Normal => &config.difficulty.normal,
+            Difficulty::Hard => &config.difficulty.hard,
+        }
+    }
+
+    fn cycle(self, step: isize) -> Self {
+        let index = Self::ALL.iter().position(|difficulty| *difficulty == self);
+        let count = Self::ALL.len() as isize;
+        let next = (index.unwrap_or_default() as isize + step).rem_euclid(count);
+
+        Self::ALL[next as usize]
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Difficulty::Easy => write!(f, "Easy"),
+            Difficulty::Normal => write!(f, "Normal"),
+            Difficulty::Hard => write!(f, "Hard"),
+        }
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "easy" => Ok(Difficulty::Easy),
+            "normal" => Ok(Difficulty::Normal),
+            "hard" => Ok(Difficulty::Hard),
+            _ => Err(format!("unknown difficulty {name:?}")),
+        }
+    }
+}
+
+/// A value that moves from `start` to `end` over the course of a curve.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Ramp {
+    pub start: f32,
+    pub end: f32,
+}
+
+impl Ramp {
+    pub const fn new(start: f32, end: f32) -> Self {
+        Self { start, end }
+    }
+
+    /// The value `progress` of the way along, from 0 to 1.
+    pub fn at(&self, progress: f32) -> f32 {
+        self.start + (self.end - self.start) * progress
+    }
+}
+
+/// How pipe pacing changes between a score of zero and `full_score`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DifficultyCurve {
+    /// Score at which the curve reaches its end values and levels off.
+    pub full_score: f32,
+    /// Multiplier on the config's `pipe_speed`.
+    pub pipe_speed: Ramp,
+    /// Multiplier on the config's `time_between_spawn`.
+    pub spawn_interval: Ramp,
+    /// Height of the opening between a pipe pair.
+    pub gap_size: Ramp,
+    /// Share of the `gap_height_min..gap_height_max` range, around its
+    /// middle, that gaps are placed in.
+    pub vertical_variance: Ramp,
+    /// Share of obstacles that move on their own; see [`crate::motion`].
+    #[serde(default)]
+    pub moving_share: Ramp,
+}
+
+impl DifficultyCurve {
+    pub fn pacing(&self, config: &GameConfig, score: usize) -> Pacing {
+        let progress = (score as f32 / self.full_score).clamp(0.0, 1.0);
+
+        Pacing {
+            pipe_speed: config.pipe_speed * self.pipe_speed.at(progress),
+            spawn_interval: config.time_between_spawn * self.spawn_interval.at(progress),
+            gap_size: self.gap_size.at(progress),
+            vertical_variance: self.vertical_variance.at(progress),
+            moving_share: self.moving_share.at(progress),
+        }
+    }
+
+    fn validate(&self, name: &str) -> Result<(), String> {
+        if !self.full_score.is_finite() || self.full_score <= 0.0 {
+            return Err(format!("{name}.full_score must be greater than zero"));
+        }
+
+        let positive = [
+            ("pipe_speed", self.pipe_speed),
+            ("spawn_interval", self.spawn_interval),
+            ("gap_size", self.gap_size),
+        ];
+
+        for (field, ramp) in positive {
+            for value in [ramp.start, ramp.end] {
+                if !value.is_finite() || value <= 0.0 {
+                    return Err(format!(
+                        "{name}.{field} must be greater than zero, got {value}"
+                    ));
+                }
+            }
+        }
+
+        let shares = [
+            ("vertical_variance", self.vertical_variance),
+            ("moving_share", self.moving_share),
+        ];
+
+        for (field, ramp) in shares {
+            for value in [ramp.start, ramp.end] {
+                if !(0.0..=1.0).contains(&value) {
+                    return Err(format!(
+                        "{name}.{field} must be between 0 and 1, got {value}"
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// One curve per [`Difficulty`] preset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DifficultyCurves {
+    pub easy: DifficultyCurve,
+    pub normal: DifficultyCurve,
+    pub hard: DifficultyCurve,
+}
+
+impl Default for DifficultyCurves {
+    fn default() -> Self {
+        Self {
+            easy: DifficultyCurve {
+                full_score: 4000.0,
+                pipe_speed: Ramp::new(0.85, 1.1),
+                spawn_interval: Ramp::new(1.2, 1.0),
+                gap_size: Ramp::new(260.0, 220.0),
+                vertical_variance: Ramp::new(0.5, 0.8),
+                moving_share: Ramp::new(0.0, 0.15),
+            },
+            normal: DifficultyCurve {
+                full_score: 3000.0,
+                pipe_speed: Ramp::new(1.0, 1.4),
+                spawn_interval: Ramp::new(1.0, 0.8),
+                gap_size: Ramp::new(200.0, 160.0),
+                vertical_variance: Ramp::new(0.7, 1.0),
+                moving_share: Ramp::new(0.0, 0.35),
+            },
+            hard: DifficultyCurve {
+                full_score: 2000.0,
+                pipe_speed: Ramp::new(1.2, 1.7),
+                spawn_interval: Ramp::new(0.9, 0.7),
+                gap_size: Ramp::new(170.0, 130.0),
+                vertical_variance: Ramp::new(1.0, 1.0),
+                moving_share: Ramp::new(0.1, 0.5),
+            },
+        }
+    }
+}
+
+impl DifficultyCurves {
+    pub fn validate(&self) -> Result<(), String> {
+        self.easy.validate("easy")?;
+        self.normal.validate("normal")?;
+        self.hard.validate("hard")
+    }
+}
+
+/// Pipe settings at a particular point along a difficulty curve.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pacing {
+    pub pipe_speed: f32,
+    pub spawn_interval: f32,
+    pub gap_size: f32,
+    pub vertical_variance: f32,
+    pub moving_share: f32,
+}
+
+/// The game config, along with the [`Pacing`] it calls for right now.
+#[derive(SystemParam)]
+pub struct CurrentPacing<'w> {
+    pub config: Res<'w, GameConfig>,
+    difficulty: Res<'w, Difficulty>,
+    score: Res<'w, Score>,
+}
+
+impl CurrentPacing<'_> {
+    pub fn get(&self) -> Pacing {
+        self.difficulty
+            .curve(&self.config)
+            .pacing(&self.config, self.score.value())
+    }
+}
+
+fn apply_pacing(
+    pacing: CurrentPacing,
+    mut obstacles: Query<&mut Velocity, With<ObstacleMarker>>,
+    mut spawn_timer: ResMut<ObstacleSpawnTimer>,
+) {
+    let pacing = pacing.get();
+
+    // Every pipe on screen speeds up together, or they'd bunch up.
+    for mut velocity in obstacles.iter_mut() {
+        velocity.linvel.x = -pacing.pipe_speed;
+    }
+
+    spawn_timer
+        .timer
+        .set_duration(Duration::from_secs_f32(pacing.spawn_interval));
+}
+
+#[derive(Debug, Component)]
+pub struct DifficultySelectorMarker;
+
+fn spawn_difficulty_selector(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Difficulty selector"),
+        DifficultySelectorMarker,
+        Text::default(),
+        TextFont::from_font_size(28.0),
+        TextLayout::new_with_justify(JustifyText::Center),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(40.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        StateScoped(AppState::Menu),
+    ));
+}
+
+fn select_difficulty(keys: Res<ButtonInput<KeyCode>>, mut difficulty: ResMut<Difficulty>) {
+    if keys.just_pressed(KeyCode::ArrowLeft) {
+        *difficulty = difficulty.cycle(-1);
+    }
+
+    if keys.just_pressed(KeyCode::ArrowRight) {
+        *difficulty = difficulty.cycle(1);
+    }
+}
+
+fn update_difficulty_selector(
+    difficulty: Res<Difficulty>,
+    mut selector: Query<&mut Text, With<DifficultySelectorMarker>>,
+) {
+    let Ok(mut selector) = selector.get_single_mut() else {
+        return;
+    };
+
+    selector.0 = format!("< {} >", *difficulty);
+}
@@ -0,0 +1,586 @@
+//! A mouse-driven editor for [`Level`] files.
+//!
+//! Obstacles are laid out left to right along a timeline that scrolls with
+//! the mouse wheel or arrow keys. Dragging a pipe pair moves it, dragging the
+//! edge of its gap resizes it, and right clicking adds or removes one. A
+//! play-test runs the level from the selected obstacle and comes back here
+//! once it's over.
+
+use crate::{
+    config::GameConfig,
+    game::Playfield,
+    levels::{ActiveLevel, Level, LevelObstacle, PickupKind},
+    motion::{ObstacleKind, SHIFT_DISTANCE},
+    obstacles::{GAP_OFFSET, Gap},
+    *,
+};
+use bevy::{
+    ecs::system::SystemParam,
+    input::mouse::{AccumulatedMouseScroll, MouseScrollUnit},
+    window::PrimaryWindow,
+};
+use std::path::PathBuf;
+
+pub struct EditorPlugin;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, application: &mut App) {
+        application
+            .add_systems(
+                OnEnter(AppState::Menu),
+                return_to_editor.run_if(resource_exists::<LevelEditor>),
+            )
+            .add_systems(
+                OnEnter(AppState::Editor),
+                (spawn_editor_hud, refresh_editor),
+            )
+            .add_systems(
+                Update,
+                (
+                    scroll_timeline,
+                    edit_with_mouse,
+                    edit_with_keys,
+                    redraw_level.run_if(resource_exists_and_changed::<LevelEditor>),
+                    update_editor_hud.run_if(resource_exists_and_changed::<LevelEditor>),
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Editor)),
+            );
+    }
+}
+
+/// Screen space left of the timeline's start.
+const TIMELINE_MARGIN: f32 = 120.0;
+/// World units the timeline scrolls per second while an arrow key is held.
+const SCROLL_SPEED: f32 = 600.0;
+/// World units the timeline scrolls per line of mouse wheel.
+const SCROLL_PER_LINE: f32 = 40.0;
+/// Distance from a gap's edge that grabs it for resizing.
+const EDGE_GRAB: f32 = 10.0;
+/// Extra room either side of a pipe that still picks it.
+const PIPE_GRAB: f32 = 8.0;
+const MIN_GAP_SIZE: f32 = 60.0;
+/// Gap size for the first obstacle of an empty level.
+const NEW_GAP_SIZE: f32 = 220.0;
+const TICK_SPACING: f32 = 100.0;
+
+const SELECTED_TINT: Color = Color::srgb(1.0, 0.7, 0.7);
+const COIN_COLOR: Color = Color::srgb(1.0, 0.8, 0.1);
+
+/// The level being edited, and where it's saved to.
+///
+/// It's kept around during play-tests, which return to the editor instead
+/// of the menu, and removed once the player leaves the editor.
+#[derive(Debug, Clone, Resource)]
+pub struct LevelEditor {
+    pub level: Level,
+    pub path: PathBuf,
+    /// How far along the timeline the left of the view is.
+    pub scroll: f32,
+    pub selected: Option<usize>,
+    drag: Option<Drag>,
+    status: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Drag {
+    /// Moving an obstacle, holding it `grab` away from its gap's middle.
+    Move {
+        index: usize,
+        grab: Vec2,
+    },
+    Resize {
+        index: usize,
+    },
+}
+
+impl LevelEditor {
+    pub fn new(level: Level, path: PathBuf) -> Self {
+        Self {
+            level,
+            path,
+            scroll: 0.0,
+            selected: None,
+            drag: None,
+            status: String::new(),
+        }
+    }
+
+    /// Opens `path` for editing, starting an empty level if it doesn't
+    /// exist yet.
+    pub fn open(path: PathBuf) -> Result<Self, levels::LevelError> {
+        let level = if path.exists() {
+            Level::load(&path)?
+        } else {
+            let name = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .map_or("Untitled", |stem| stem.trim_end_matches(".level"))
+                .to_string();
+
+            Level {
+                name,
+                pipe_speed: None,
+                obstacles: Vec::new(),
+            }
+        };
+
+        Ok(Self::new(level, path))
+    }
+
+    /// Timeline position of every obstacle.
+    fn positions(&self) -> Vec<f32> {
+        self.level
+            .obstacles
+            .iter()
+            .scan(0.0, |position, obstacle| {
+                *position += obstacle.spacing;
+                Some(*position)
+            })
+            .collect()
+    }
+
+    /// The obstacle whose pipes are under `point` on the timeline, and
+    /// whether `point` is on the edge of its gap.
+    fn pick(&self, point: Vec2, half_width: f32) -> Option<(usize, bool)> {
+        let positions = self.positions();
+
+        self.level
+            .obstacles
+            .iter()
+            .zip(positions)
+            .enumerate()
+            .find(|(_, (_, position))| (point.x - position).abs() <= half_width + PIPE_GRAB)
+            .map(|(index, (obstacle, _))| {
+                let edge = (point.y - obstacle.gap_y).abs() - obstacle.gap_size / 2.0;
+                (index, edge.abs() <= EDGE_GRAB)
+            })
+    }
+
+    /// Moves obstacle `index` to timeline position `position`, keeping it
+    /// between its neighbours so the ones after it stay where they are.
+    fn move_to(&mut self, index: usize, position: f32) {
+        let positions = self.positions();
+        let previous = index.checked_sub(1).map_or(0.0, |before| positions[before]);
+        let next = positions.get(index + 1).copied();
+        let position = position.max(previous).min(next.unwrap_or(f32::INFINITY));
+
+        self.level.obstacles[index].spacing = position - previous;
+        if let Some(next) = next {
+            self.level.obstacles[index + 1].spacing = next - position;
+        }
+    }
+
+    fn insert_at(&mut self, point: Vec2) -> usize {
+        let positions = self.positions();
+        let index = positions
+            .iter()
+            .position(|position| *position > point.x)
+            .unwrap_or(positions.len());
+        let previous = index.checked_sub(1).map_or(0.0, |before| positions[before]);
+        let position = point.x.max(previous);
+
+        let gap_size = index
+            .checked_sub(1)
+            .or((index < positions.len()).then_some(index))
+            .map_or(NEW_GAP_SIZE, |neighbour| {
+                self.level.obstacles[neighbour].gap_size
+            });
+
+        let obstacle = LevelObstacle {
+            spacing: position - previous,
+            gap_y: point.y,
+            gap_size,
+            kind: ObstacleKind::Static,
+            pickup: None,
+        };
+
+        if let Some(next) = self.level.obstacles.get_mut(index) {
+            next.spacing -= obstacle.spacing;
+        }
+        self.level.obstacles.insert(index, obstacle);
+
+        index
+    }
+
+    fn remove(&mut self, index: usize) {
+        let removed = self.level.obstacles.remove(index);
+
+        // Close up the space so the obstacles after it stay put.
+        if let Some(next) = self.level.obstacles.get_mut(index) {
+            next.spacing += removed.spacing;
+        }
+
+        self.selected = None;
+        self.drag = None;
+    }
+
+    /// The level from the selected obstacle onwards, for a play-test.
+    fn play_test_level(&self) -> Option<Level> {
+        let start = self.selected.unwrap_or_else(|| {
+            let positions = self.positions();
+            positions
+                .iter()
+                .position(|position| *position >= self.scroll)
+                .unwrap_or_default()
+        });
+
+        let mut obstacles = self.level.obstacles.get(start..)?.to_vec();
+        obstacles.first_mut()?.spacing = 0.0;
+
+        Some(Level {
+            obstacles,
+            ..self.level.clone()
+        })
+    }
+}
+
+/// Screen x of the start of the timeline, before scrolling.
+fn timeline_origin(playfield: &Playfield) -> f32 {
+    -playfield.width / 2.0 + TIMELINE_MARGIN
+}
+
+/// The mouse cursor, in world space.
+#[derive(SystemParam)]
+struct EditorCursor<'w, 's> {
+    windows: Query<'w, 's, &'static Window, With<PrimaryWindow>>,
+    cameras: Query<'w, 's, (&'static Camera, &'static GlobalTransform)>,
+}
+
+impl EditorCursor<'_, '_> {
+    fn world_position(&self) -> Option<Vec2> {
+        let cursor = self.windows.get_single().ok()?.cursor_position()?;
+        let (camera, transform) = self.cameras.get_single().ok()?;
+
+        camera.viewport_to_world_2d(transform, cursor).ok()
+    }
+}
+
+fn return_to_editor(mut next_state: ResMut<NextState<AppState>>) {
+    next_state.set(AppState::Editor);
+}
+
+/// Redraws everything on the way in, since leaving cleared it.
+fn refresh_editor(mut editor: ResMut<LevelEditor>) {
+    editor.set_changed();
+}
+
+fn scroll_timeline(
+    time: Res<Time>,
+    keys: Res<ButtonInput<KeyCode>>,
+    wheel: Res<AccumulatedMouseScroll>,
+    mut editor: ResMut<LevelEditor>,
+) {
+    let lines = match wheel.unit {
+        MouseScrollUnit::Line => wheel.delta.y,
+        MouseScrollUnit::Pixel => wheel.delta.y / SCROLL_PER_LINE,
+    };
+    let mut scroll = -lines * SCROLL_PER_LINE;
+
+    if keys.pressed(KeyCode::ArrowRight) {
+        scroll += SCROLL_SPEED * time.delta_secs();
+    }
+    if keys.pressed(KeyCode::ArrowLeft) {
+        scroll -= SCROLL_SPEED * time.delta_secs();
+    }
+
+    // Only touch the editor when scrolling, so it's redrawn only then.
+    if scroll != 0.0 {
+        editor.scroll = (editor.scroll + scroll).max(0.0);
+    }
+}
+
+fn edit_with_mouse(
+    buttons: Res<ButtonInput<MouseButton>>,
+    cursor: EditorCursor,
+    mut editor: ResMut<LevelEditor>,
+    config: Res<GameConfig>,
+    playfield: Res<Playfield>,
+) {
+    if buttons.just_released(MouseButton::Left) {
+        editor.drag = None;
+    }
+
+    let Some(cursor) = cursor.world_position() else {
+        return;
+    };
+    let point = Vec2::new(
+        cursor.x - timeline_origin(&playfield) + editor.scroll,
+        cursor.y,
+    );
+
+    if buttons.just_pressed(MouseButton::Left) {
+        let picked = editor.pick(point, config.obstacle_width);
+
+        editor.selected = picked.map(|(index, _)| index);
+        editor.drag = picked.map(|(index, on_edge)| {
+            if on_edge {
+                Drag::Resize { index }
+            } else {
+                let obstacle = &editor.level.obstacles[index];
+                let position = editor.positions()[index];
+                Drag::Move {
+                    index,
+                    grab: point - Vec2::new(position, obstacle.gap_y),
+                }
+            }
+        });
+    }
+
+    if buttons.just_pressed(MouseButton::Right) {
+        match editor.pick(point, config.obstacle_width) {
+            Some((index, _)) => editor.remove(index),
+            None => {
+                let index = editor.insert_at(point);
+                editor.selected = Some(index);
+            }
+        }
+    }
+
+    if !buttons.pressed(MouseButton::Left) {
+        return;
+    }
+
+    match editor.drag {
+        Some(Drag::Move { index, grab }) => {
+            let target = point - grab;
+            editor.move_to(index, target.x);
+            editor.level.obstacles[index].gap_y = target.y;
+        }
+        Some(Drag::Resize { index }) => {
+            let obstacle = &mut editor.level.obstacles[index];
+            obstacle.gap_size = (2.0 * (point.y - obstacle.gap_y).abs()).max(MIN_GAP_SIZE);
+        }
+        None => {}
+    }
+}
+
+fn edit_with_keys(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut editor: ResMut<LevelEditor>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if keys.just_pressed(KeyCode::Escape) {
+        commands.remove_resource::<LevelEditor>();
+        next_state.set(AppState::Menu);
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::KeyS) {
+        editor.status = match editor.level.save(&editor.path) {
+            Ok(()) => format!("Saved to {}", editor.path.display()),
+            Err(error) => error.to_string(),
+        };
+    }
+
+    if keys.just_pressed(KeyCode::KeyP) {
+        match editor.play_test_level() {
+            Some(level) => {
+                commands.insert_resource(ActiveLevel::new(level));
+                next_state.set(AppState::InGame);
+            }
+            None => editor.status = "Add an obstacle to play-test".to_string(),
+        }
+    }
+
+    let Some(index) = editor.selected else {
+        return;
+    };
+
+    if keys.any_just_pressed([KeyCode::Delete, KeyCode::Backspace]) {
+        editor.remove(index);
+        return;
+    }
+
+    // Only borrow the obstacle mutably when a key changes it, as any mutable
+    // access marks the editor changed and has the whole level redrawn.
+    if !keys.any_just_pressed([KeyCode::KeyK, KeyCode::KeyC]) {
+        return;
+    }
+    let obstacle = &mut editor.level.obstacles[index];
+
+    if keys.just_pressed(KeyCode::KeyK) {
+        obstacle.kind = match obstacle.kind {
+            ObstacleKind::Static => ObstacleKind::Oscillating,
+            ObstacleKind::Oscillating => ObstacleKind::Breathing,
+            ObstacleKind::Breathing => ObstacleKind::Shifting(SHIFT_DISTANCE),
+            ObstacleKind::Shifting(distance) if distance > 0.0 => {
+                ObstacleKind::Shifting(-SHIFT_DISTANCE)
+            }
+            ObstacleKind::Shifting(_) => ObstacleKind::Static,
+        };
+    }
+
+    if keys.just_pressed(KeyCode::KeyC) {
+        obstacle.pickup = match obstacle.pickup {
+            Some(_) => None,
+            None => Some(PickupKind::Coin),
+        };
+    }
+}
+
+/// Part of the drawn level, cleared out whenever it's redrawn.
+#[derive(Debug, Component)]
+pub struct EditorDrawing;
+
+fn redraw_level(
+    mut commands: Commands,
+    editor: Res<LevelEditor>,
+    drawings: Query<Entity, With<EditorDrawing>>,
+    assets: Res<SpriteAssets>,
+    playfield: Res<Playfield>,
+) {
+    for drawing in drawings.iter() {
+        commands.entity(drawing).despawn_recursive();
+    }
+
+    let origin = timeline_origin(&playfield) - editor.scroll;
+    let ground = -playfield.height / 2.0;
+
+    // Distance markers along the bottom, so spacing can be judged by eye.
+    let first_tick = (editor.scroll / TICK_SPACING).floor() as i32;
+    let tick_count = (playfield.width / TICK_SPACING).ceil() as i32 + 1;
+    for tick in first_tick..first_tick + tick_count {
+        let x = origin + tick as f32 * TICK_SPACING;
+        let height = if tick % 5 == 0 { 24.0 } else { 12.0 };
+
+        commands.spawn((
+            Name::new("Timeline tick"),
+            EditorDrawing,
+            Sprite::from_color(Color::WHITE, Vec2::new(2.0, height)),
+            Transform::from_xyz(x, ground + height / 2.0, 2.0),
+            StateScoped(AppState::Editor),
+        ));
+    }
+
+    for (index, (obstacle, position)) in editor
+        .level
+        .obstacles
+        .iter()
+        .zip(editor.positions())
+        .enumerate()
+    {
+        let gap = Gap::with_size(obstacle.gap_size);
+        let tint = if editor.selected == Some(index) {
+            SELECTED_TINT
+        } else {
+            Color::WHITE
+        };
+
+        let mut top = Sprite::from_image(assets.green_pipe.clone());
+        top.flip_y = true;
+        top.color = tint;
+        let mut bottom = Sprite::from_image(assets.green_pipe.clone());
+        bottom.color = tint;
+
+        commands
+            .spawn((
+                Name::new("Editor obstacle"),
+                EditorDrawing,
+                Transform::from_xyz(origin + position, obstacle.gap_y - gap.offset, 0.0),
+                Visibility::Visible,
+                StateScoped(AppState::Editor),
+            ))
+            .with_children(|parent| {
+                parent.spawn((top, Transform::from_xyz(0.0, gap.top_pipe_offset(), 0.0)));
+                parent.spawn((
+                    bottom,
+                    Transform::from_xyz(0.0, gap.bottom_pipe_offset(), 0.0),
+                ));
+
+                if obstacle.pickup.is_some() {
+                    parent.spawn((
+                        Sprite::from_color(COIN_COLOR, Vec2::splat(24.0)),
+                        Transform::from_xyz(0.0, GAP_OFFSET, 1.0),
+                    ));
+                }
+
+                if obstacle.kind != ObstacleKind::Static {
+                    parent.spawn((
+                        Text2d::new(kind_label(obstacle.kind)),
+                        TextFont::from_font_size(16.0),
+                        Transform::from_xyz(0.0, GAP_OFFSET + obstacle.gap_size / 2.0 + 16.0, 1.0),
+                    ));
+                }
+            });
+    }
+}
+
+fn kind_label(kind: ObstacleKind) -> &'static str {
+    match kind {
+        ObstacleKind::Static => "static",
+        ObstacleKind::Oscillating => "oscillating",
+        ObstacleKind::Breathing => "breathing",
+        ObstacleKind::Shifting(distance) if distance > 0.0 => "shifts up",
+        ObstacleKind::Shifting(_) => "shifts down",
+    }
+}
+
+#[derive(Debug, Component)]
+pub struct EditorHudMarker;
+
+fn spawn_editor_hud(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Editor help"),
+        Text::new(
+            "Drag: move   Drag gap edge: resize   Right click: add/remove\n\
+             Wheel/arrows: scroll   K: kind   C: coin   Del: delete\n\
+             P: play-test from selected   S: save   Esc: leave",
+        ),
+        TextFont::from_font_size(14.0),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(40.0),
+            left: Val::Px(10.0),
+            ..default()
+        },
+        StateScoped(AppState::Editor),
+    ));
+
+    commands.spawn((
+        Name::new("Editor status"),
+        EditorHudMarker,
+        Text::default(),
+        TextFont::from_font_size(18.0),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            left: Val::Px(10.0),
+            ..default()
+        },
+        StateScoped(AppState::Editor),
+    ));
+}
+
+fn update_editor_hud(editor: Res<LevelEditor>, mut hud: Query<&mut Text, With<EditorHudMarker>>) {
+    let Ok(mut hud) = hud.get_single_mut() else {
+        return;
+    };
+
+    let mut text = format!(
+        "{} - {} obstacles",
+        editor.level.name,
+        editor.level.obstacles.len()
+    );
+
+    if let Some(obstacle) = editor
+        .selected
+        .and_then(|index| editor.level.obstacles.get(index))
+    {
+        text.push_str(&format!(
+            "\nSpacing {:.0}  Gap y {:.0}  Gap size {:.0}  {}",
+            obstacle.spacing,
+            obstacle.gap_y,
+            obstacle.gap_size,
+            kind_label(obstacle.kind)
+        ));
+    }
+
+    if !editor.status.is_empty() {
+        text.push('\n');
+        text.push_str(&editor.status);
+    }
+
+    hud.0 = text;
+}
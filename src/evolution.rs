@@ -0,0 +1,463 @@
+//! Neuroevolution: a population of birds, each flown by a tiny neural
+//! network, evolving against the same seeded pipes generation after
+//! generation.
+
+use crate::{
+    bird::{BirdAtlas, BirdMarker, Flap, spawn_bird},
+    collision::{BirdHits, CollisionSet},
+    config::GameConfig,
+    game::{GameMode, PendingSeed, Playfield, RunSeed, RunTick, Score},
+    obstacles::{Gap, ObstacleNotScored},
+    *,
+};
+use bevy_rapier2d::prelude::*;
+use rand::Rng;
+use rand_core::SeedableRng;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+/// Champion files of any other version are refused, as their network may
+/// not fit the current inputs.
+pub const CHAMPION_VERSION: u32 = 1;
+
+pub const INPUTS: usize = 5;
+pub const HIDDEN: usize = 6;
+/// Weights for both layers, each neuron carrying an extra bias weight.
+pub const GENOME_LENGTH: usize = (INPUTS + 1) * HIDDEN + (HIDDEN + 1);
+
+/// Share of each generation carried over unchanged.
+const ELITE_FRACTION: f32 = 0.2;
+const MUTATION_RATE: f32 = 0.2;
+const MUTATION_STRENGTH: f32 = 0.5;
+
+/// A generation is cut short after this many seconds of game time, so a
+/// perfect flyer can't stall training forever.
+const MAX_GENERATION_SECONDS: f64 = 180.0;
+const MAX_GENERATION_TICKS: u64 = (TICK_RATE_HZ * MAX_GENERATION_SECONDS) as u64;
+
+pub struct EvolutionPlugin {
+    pub population: usize,
+    /// Where the best network is saved, and loaded from on start up.
+    pub champion_path: Option<PathBuf>,
+}
+
+impl Plugin for EvolutionPlugin {
+    fn build(&self, application: &mut App) {
+        let seed = application
+            .world()
+            .get_resource::<RunSeed>()
+            .map_or(0, |seed| seed.0);
+        let champion = self
+            .champion_path
+            .as_ref()
+            .filter(|path| path.exists())
+            .and_then(|path| match Champion::load(path) {
+                Ok(champion) => Some(champion),
+                Err(error) => {
+                    warn!("Not starting from a saved champion: {error}");
+                    None
+                }
+            });
+
+        application
+            .insert_resource(GameMode::Training)
+            .insert_resource(Evolution::new(
+                self.population,
+                seed,
+                champion,
+                self.champion_path.clone(),
+            ))
+            .add_systems(
+                OnEnter(AppState::InGame),
+                (spawn_population, spawn_training_ui),
+            )
+            .add_systems(
+                FixedPreUpdate,
+                think
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(in_state(GameState::Running)),
+            )
+            .add_systems(
+                FixedUpdate,
+                (kill_birds, end_generation)
+                    .chain()
+                    .after(CollisionSet)
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(in_state(GameState::Running)),
+            )
+            .add_systems(
+                Update,
+                update_training_ui.run_if(in_state(AppState::InGame)),
+            );
+    }
+}
+
+/// The weights of a single bird's network.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Genome {
+    pub weights: Vec<f32>,
+}
+
+impl Genome {
+    pub fn random(rng: &mut impl Rng) -> Self {
+        Self {
+            weights: (0..GENOME_LENGTH)
+                .map(|_| rng.gen_range(-1.0..1.0))
+                .collect(),
+        }
+    }
+
+    /// Runs the network, returning whether the bird should flap.
+    pub fn should_flap(&self, inputs: [f32; INPUTS]) -> bool {
+        let (hidden_weights, output_weights) = self.weights.split_at((INPUTS + 1) * HIDDEN);
+
+        let hidden: Vec<f32> = hidden_weights
+            .chunks(INPUTS + 1)
+            .map(|neuron| {
+                let sum: f32 = neuron.iter().zip(inputs).map(|(w, x)| w * x).sum();
+                (sum + neuron[INPUTS]).tanh()
+            })
+            .collect();
+
+        let output: f32 = output_weights
+            .iter()
+            .zip(&hidden)
+            .map(|(w, x)| w * x)
+            .sum::<f32>()
+            + output_weights[HIDDEN];
+
+        output > 0.0
+    }
+
+    fn mutated(&self, rng: &mut impl Rng) -> Self {
+        Self {
+            weights: self
+                .weights
+                .iter()
+                .map(|weight| {
+                    if rng.gen_range(0.0..1.0) < MUTATION_RATE {
+                        weight + rng.gen_range(-MUTATION_STRENGTH..MUTATION_STRENGTH)
+                    } else {
+                        *weight
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The best network found so far, as saved to disk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Champion {
+    pub version: u32,
+    pub generation: usize,
+    pub score: usize,
+    pub genome: Genome,
+}
+
+impl Champion {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ChampionError> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .map_err(|error| ChampionError::Io(path.to_path_buf(), error))?;
+        let champion: Champion = ron::from_str(&contents).map_err(ChampionError::Parse)?;
+
+        if champion.version != CHAMPION_VERSION {
+            return Err(ChampionError::UnsupportedVersion(champion.version));
+        }
+        if champion.genome.weights.len() != GENOME_LENGTH {
+            return Err(ChampionError::WrongShape(champion.genome.weights.len()));
+        }
+
+        Ok(champion)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ChampionError> {
+        let path = path.as_ref();
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(ChampionError::Serialize)?;
+
+        fs::write(path, contents).map_err(|error| ChampionError::Io(path.to_path_buf(), error))
+    }
+}
+
+#[derive(Debug)]
+pub enum ChampionError {
+    Io(PathBuf, std::io::Error),
+    Parse(ron::error::SpannedError),
+    Serialize(ron::Error),
+    UnsupportedVersion(u32),
+    WrongShape(usize),
+}
+
+impl fmt::Display for ChampionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChampionError::Io(path, error) => write!(f, "{}: {error}", path.display()),
+            ChampionError::Parse(error) => write!(f, "invalid champion file: {error}"),
+            ChampionError::Serialize(error) => write!(f, "could not write champion: {error}"),
+            ChampionError::UnsupportedVersion(version) => write!(
+                f,
+                "champion version {version} is not supported (expected {CHAMPION_VERSION})"
+            ),
+            ChampionError::WrongShape(length) => write!(
+                f,
+                "champion has {length} weights, but the network needs {GENOME_LENGTH}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ChampionError {}
+
+/// Training progress: the current population and how each member did.
+#[derive(Resource)]
+pub struct Evolution {
+    pub generation: usize,
+    pub genomes: Vec<Genome>,
+    /// Fitness and score of each genome this generation, once it has died.
+    pub results: Vec<Option<(f32, usize)>>,
+    /// Score of the best genome in the previous generation.
+    pub last_generation_score: usize,
+    pub best_score: usize,
+    pub champion: Option<Champion>,
+    champion_path: Option<PathBuf>,
+    rng: WyRand,
+}
+
+impl Evolution {
+    fn new(
+        population: usize,
+        seed: u64,
+        champion: Option<Champion>,
+        champion_path: Option<PathBuf>,
+    ) -> Self {
+        let population = population.max(1);
+        // Seeded separately from the pipes, which replay the same layout every
+        // generation.
+        let mut rng = WyRand::seed_from_u64(seed);
+        let genomes: Vec<Genome> = match &champion {
+            Some(champion) => std::iter::once(champion.genome.clone())
+                .chain((1..population).map(|_| champion.genome.mutated(&mut rng)))
+                .collect(),
+            None => (0..population).map(|_| Genome::random(&mut rng)).collect(),
+        };
+
+        Self {
+            generation: 1,
+            results: vec![None; genomes.len()],
+            genomes,
+            last_generation_score: 0,
+            best_score: champion.as_ref().map_or(0, |champion| champion.score),
+            champion,
+            champion_path,
+            rng,
+        }
+    }
+
+    pub fn alive(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|result| result.is_none())
+            .count()
+    }
+
+    fn next_generation(&mut self) {
+        let mut ranked: Vec<(Genome, f32, usize)> = self
+            .genomes
+            .drain(..)
+            .zip(&self.results)
+            .map(|(genome, result)| {
+                let (fitness, score) = result.unwrap_or_default();
+                (genome, fitness, score)
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        let (best, _, best_score) = ranked[0].clone();
+        info!(
+            "Generation {} best score {} (best ever {})",
+            self.generation,
+            best_score,
+            self.best_score.max(best_score)
+        );
+
+        self.last_generation_score = best_score;
+
+        if self.champion.is_none() || best_score > self.best_score {
+            self.best_score = best_score;
+            self.save_champion(best, best_score);
+        }
+
+        let population = ranked.len();
+        let elites = ((population as f32 * ELITE_FRACTION).ceil() as usize).clamp(1, population);
+        let mut next: Vec<Genome> = ranked
+            .iter()
+            .take(elites)
+            .map(|(genome, _, _)| genome.clone())
+            .collect();
+        while next.len() < population {
+            let parent = &ranked[self.rng.gen_range(0..elites)].0;
+            next.push(parent.mutated(&mut self.rng));
+        }
+
+        self.generation += 1;
+        self.results = vec![None; next.len()];
+        self.genomes = next;
+    }
+
+    fn save_champion(&mut self, genome: Genome, score: usize) {
+        let champion = Champion {
+            version: CHAMPION_VERSION,
+            generation: self.generation,
+            score,
+            genome,
+        };
+
+        if let Some(path) = &self.champion_path
+            && let Err(error) = champion.save(path)
+        {
+            error!("Failed to save champion: {error}");
+        }
+
+        self.champion = Some(champion);
+    }
+}
+
+/// Which genome in [`Evolution::genomes`] flies this bird.
+#[derive(Debug, Clone, Copy, Component)]
+pub struct Brain(pub usize);
+
+fn spawn_population(
+    mut commands: Commands,
+    assets: Res<SpriteAssets>,
+    atlas: Res<BirdAtlas>,
+    config: Res<GameConfig>,
+    evolution: Res<Evolution>,
+) {
+    for index in 0..evolution.genomes.len() {
+        let bird = spawn_bird(&mut commands, &assets, &atlas, &config, Color::WHITE);
+
+        commands.entity(bird).insert(Brain(index));
+    }
+}
+
+fn think(
+    birds: Query<(Entity, &Transform, &Velocity, &Brain), With<BirdMarker>>,
+    obstacles: Query<(&Transform, &Gap), ObstacleNotScored>,
+    evolution: Res<Evolution>,
+    config: Res<GameConfig>,
+    playfield: Res<Playfield>,
+    mut flaps: EventWriter<Flap>,
+) {
+    let next_gap = obstacles
+        .iter()
+        .filter(|(transform, _)| transform.translation.x + config.obstacle_width >= config.birb_x)
+        .min_by(|(a, _), (b, _)| a.translation.x.total_cmp(&b.translation.x))
+        .map(|(transform, gap)| {
+            let (bottom, top) = gap.bounds(transform.translation.y);
+            (transform.translation.x - config.birb_x, bottom, top)
+        })
+        .unwrap_or((
+            playfield.width,
+            -playfield.height / 2.0,
+            playfield.height / 2.0,
+        ));
+
+    for (bird, transform, velocity, brain) in birds.iter() {
+        let (distance, bottom, top) = next_gap;
+        let inputs = [
+            transform.translation.y / playfield.height,
+            velocity.linvel.y / config.jump_velocity,
+            distance / playfield.width,
+            (bottom - transform.translation.y) / playfield.height,
+            (top - transform.translation.y) / playfield.height,
+        ];
+
+        if evolution.genomes[brain.0].should_flap(inputs) {
+            flaps.send(Flap { bird });
+        }
+    }
+}
+
+/// Retires birds that hit something, or every bird once the generation runs
+/// out of time.
+fn kill_birds(
+    mut commands: Commands,
+    mut hits: BirdHits,
+    birds: Query<(Entity, &Brain)>,
+    mut evolution: ResMut<Evolution>,
+    tick: Res<RunTick>,
+    score: Res<Score>,
+) {
+    let mut dying: Vec<Entity> = hits.read().map(|(bird, _)| bird).collect();
+
+    if tick.0 >= MAX_GENERATION_TICKS {
+        dying.extend(birds.iter().map(|(bird, _)| bird));
+    }
+
+    for bird in dying {
+        let Ok((_, brain)) = birds.get(bird) else {
+            continue;
+        };
+
+        if evolution.results[brain.0].is_none() {
+            evolution.results[brain.0] = Some((tick.0 as f32, score.value()));
+            commands.entity(bird).despawn_recursive();
+        }
+    }
+}
+
+fn end_generation(
+    mut evolution: ResMut<Evolution>,
+    mut pending_seed: ResMut<PendingSeed>,
+    mut next_state: ResMut<NextState<AppState>>,
+    seed: Res<RunSeed>,
+) {
+    if evolution.alive() > 0 {
+        return;
+    }
+
+    evolution.next_generation();
+    pending_seed.0 = Some(seed.0);
+    next_state.set(AppState::Restarting);
+}
+
+#[derive(Debug, Component)]
+pub struct TrainingUiMarker;
+
+fn spawn_training_ui(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Training UI"),
+        TrainingUiMarker,
+        Text::new(""),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(15.0),
+            left: Val::Px(15.0),
+            ..default()
+        },
+        StateScoped(AppState::InGame),
+    ));
+}
+
+fn update_training_ui(
+    evolution: Res<Evolution>,
+    mut text: Query<&mut Text, With<TrainingUiMarker>>,
+) {
+    let Ok(mut text) = text.get_single_mut() else {
+        return;
+    };
+
+    text.0 = format!(
+        "Generation {}\nAlive {}/{}\nLast generation best {}\nBest ever {}",
+        evolution.generation,
+        evolution.alive(),
+        evolution.genomes.len(),
+        evolution.last_generation_score,
+        evolution.best_score,
+    );
+}
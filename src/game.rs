@@ -0,0 +1,610 @@
+use crate::{
+    bird::PlayerControlled,
+    collision::{BirdHits, CeilingRule, CollisionSet, DeathCauses, Hazard},
+    *,
+};
+use bevy::audio::Volume;
+use bevy_rapier2d::prelude::*;
+use rand_core::RngCore;
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+pub struct GamePlugin {
+    pub seed: u64,
+}
+
+impl Plugin for GamePlugin {
+    fn build(&self, application: &mut App) {
+        application
+            .init_state::<AppState>()
+            .init_state::<GameState>()
+            .enable_state_scoped_entities::<AppState>()
+            .enable_state_scoped_entities::<GameState>()
+            .init_resource::<Playfield>()
+            .init_resource::<Score>()
+            .init_resource::<RunTick>()
+            .init_resource::<PendingSeed>()
+            .init_resource::<GameMode>()
+            .init_resource::<GameOverLockout>()
+            .insert_resource(RunSeed(self.seed))
+            .add_systems(OnEnter(AppState::Loading), spawn_loading_screen)
+            .add_systems(OnExit(AppState::Loading), despawn_loading_screen)
+            .add_systems(OnEnter(AppState::Menu), spawn_start_menu)
+            .add_systems(Update, start_game_on_input.run_if(in_state(AppState::Menu)))
+            .add_systems(
+                OnEnter(AppState::InGame),
+                (spawn_ground_and_ceiling, spawn_ui, reset_tick),
+            )
+            .add_systems(
+                Last,
+                exit_after_frame_limit.run_if(resource_exists::<FrameLimit>),
+            )
+            .add_systems(OnExit(AppState::InGame), advance_run_seed)
+            .add_systems(OnEnter(AppState::Restarting), restart_game)
+            .add_systems(
+                FixedLast,
+                advance_tick
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(in_state(GameState::Running)),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    end_run_on_hit
+                        .after(CollisionSet)
+                        .run_if(not(resource_equals(GameMode::Training))),
+                    player_scored.after(obstacles::score_obstacle),
+                )
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(in_state(GameState::Running)),
+            )
+            .add_systems(
+                Update,
+                (update_score, update_seed)
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(in_state(GameState::Running)),
+            )
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                (
+                    spawn_game_over_ui.run_if(not(levels::level_completed)),
+                    spawn_game_over_buttons,
+                    start_game_over_lockout,
+                ),
+            )
+            .add_systems(
+                Update,
+                (tick_game_over_lockout, (finish_game, retry_game))
+                    .chain()
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(in_state(GameState::GameOver)),
+            );
+    }
+}
+
+/// The logical size of the play area, in world units.
+///
+/// Gameplay is laid out against this rather than the primary window so the
+/// same systems work when there is no window at all.
+#[derive(Debug, Clone, Copy, Resource)]
+pub struct Playfield {
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Default for Playfield {
+    fn default() -> Self {
+        Self {
+            width: WINDOW_WIDTH,
+            height: WINDOW_HEIGHT,
+        }
+    }
+}
+
+/// The ruleset a run is played under.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Resource, Serialize, Deserialize)]
+pub enum GameMode {
+    #[default]
+    Endless,
+    /// Endless pipes laid out from the date; see [`crate::daily`].
+    Daily,
+    /// A population of bot birds learning to play; see [`crate::evolution`].
+    Training,
+}
+
+impl fmt::Display for GameMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameMode::Endless => write!(f, "Endless"),
+            GameMode::Daily => write!(f, "Daily"),
+            GameMode::Training => write!(f, "Training"),
+        }
+    }
+}
+
+impl FromStr for GameMode {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "endless" => Ok(GameMode::Endless),
+            "daily" => Ok(GameMode::Daily),
+            "training" => Ok(GameMode::Training),
+            _ => Err(format!("unknown game mode {name:?}")),
+        }
+    }
+}
+
+/// Seed the pipe layout of the current run is generated from.
+///
+/// The global RNG is reseeded from this at the start of every run, so a run
+/// can be reproduced from its seed alone.
+#[derive(Debug, Clone, Copy, Resource)]
+pub struct RunSeed(pub u64);
+
+/// Quits the app once this many frames have been run.
+#[derive(Debug, Clone, Copy, Resource)]
+pub struct FrameLimit(pub u32);
+
+fn exit_after_frame_limit(
+    frames: Res<bevy::core::FrameCount>,
+    limit: Res<FrameLimit>,
+    mut app_exit: EventWriter<AppExit>,
+) {
+    if frames.0 >= limit.0 {
+        app_exit.send(AppExit::Success);
+    }
+}
+
+/// A seed to start the next run with, instead of moving on to a new one.
+#[derive(Debug, Default, Clone, Copy, Resource)]
+pub struct PendingSeed(pub Option<u64>);
+
+/// Number of fixed timesteps simulated since the current run started.
+#[derive(Debug, Default, Clone, Copy, Resource)]
+pub struct RunTick(pub u64);
+
+fn reset_tick(mut tick: ResMut<RunTick>) {
+    tick.0 = 0;
+}
+
+fn advance_tick(mut tick: ResMut<RunTick>) {
+    tick.0 += 1;
+}
+
+fn advance_run_seed(mut rng: GlobalEntropy<WyRand>, mut seed: ResMut<RunSeed>) {
+    seed.0 = rng.next_u64();
+}
+
+fn restart_game(mut next_state: ResMut<NextState<AppState>>) {
+    next_state.set(AppState::InGame);
+}
+
+fn spawn_game_over_ui(mut commands: Commands, asset_server: Res<SpriteAssets>) {
+    commands.spawn((
+        Name::new("Game over ui"),
+        Sprite::from_image(asset_server.game_over.clone()),
+        // Sits above the results panel.
+        Transform::from_translation(Vec3::new(0.0, 180.0, 4.0)),
+        StateScoped(AppState::InGame),
+    ));
+}
+
+/// How long the game over screen ignores input, so a panicked flap just as
+/// the bird dies doesn't skip straight past the results.
+pub const GAME_OVER_LOCKOUT_SECONDS: f32 = 0.75;
+
+#[derive(Debug, Resource)]
+pub struct GameOverLockout(pub Timer);
+
+impl Default for GameOverLockout {
+    fn default() -> Self {
+        Self(Timer::from_seconds(
+            GAME_OVER_LOCKOUT_SECONDS,
+            TimerMode::Once,
+        ))
+    }
+}
+
+fn start_game_over_lockout(mut lockout: ResMut<GameOverLockout>) {
+    lockout.0.reset();
+}
+
+fn tick_game_over_lockout(mut lockout: ResMut<GameOverLockout>, time: Res<Time>) {
+    lockout.0.tick(time.delta());
+}
+
+fn finish_game(
+    mut input: EventReader<input::ButtonPressed>,
+    lockout: Res<GameOverLockout>,
+    buttons: Query<&Interaction, With<GameOverButton>>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if input.is_empty() {
+        return;
+    }
+    input.clear();
+
+    // Clicking a button flaps too; leave those clicks to `retry_game`.
+    let on_button = buttons
+        .iter()
+        .any(|interaction| *interaction != Interaction::None);
+    if !lockout.0.finished() || on_button {
+        return;
+    }
+
+    next_state.set(AppState::Menu);
+}
+
+/// What to do once a run is over, besides flapping back to the menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+pub enum GameOverButton {
+    /// Start another run on a fresh seed.
+    Retry,
+    /// Fly the same pipes again.
+    RetrySameSeed,
+    Menu,
+}
+
+impl GameOverButton {
+    fn label(&self) -> &'static str {
+        match self {
+            GameOverButton::Retry => "Retry (R)",
+            GameOverButton::RetrySameSeed => "Same seed (T)",
+            GameOverButton::Menu => "Menu",
+        }
+    }
+
+    fn key(&self) -> Option<KeyCode> {
+        match self {
+            GameOverButton::Retry => Some(KeyCode::KeyR),
+            GameOverButton::RetrySameSeed => Some(KeyCode::KeyT),
+            GameOverButton::Menu => None,
+        }
+    }
+}
+
+fn spawn_game_over_buttons(mut commands: Commands) {
+    commands
+        .spawn((
+            Name::new("Game over buttons"),
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(760.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                column_gap: Val::Px(15.0),
+                ..default()
+            },
+            StateScoped(AppState::InGame),
+        ))
+        .with_children(|parent| {
+            for button in [
+                GameOverButton::Retry,
+                GameOverButton::RetrySameSeed,
+                GameOverButton::Menu,
+            ] {
+                parent
+                    .spawn((
+                        Name::new(button.label()),
+                        button,
+                        Button,
+                        Node {
+                            padding: UiRect::all(Val::Px(10.0)),
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
+                    ))
+                    .with_children(|button_parent| {
+                        button_parent.spawn(Text::new(button.label()));
+                    });
+            }
+        });
+}
+
+/// Goes straight into the next run through [`AppState::Restarting`], which
+/// clears out the old run's entities and resets everything else on the way
+/// back into the game, exactly as starting from the menu would.
+fn retry_game(
+    keys: Res<ButtonInput<KeyCode>>,
+    buttons: Query<(&Interaction, &GameOverButton), Changed<Interaction>>,
+    lockout: Res<GameOverLockout>,
+    seed: Res<RunSeed>,
+    mut pending_seed: ResMut<PendingSeed>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if !lockout.0.finished() {
+        return;
+    }
+
+    let pressed_key = [GameOverButton::Retry, GameOverButton::RetrySameSeed]
+        .into_iter()
+        .find(|button| button.key().is_some_and(|key| keys.just_pressed(key)));
+    let clicked = buttons
+        .iter()
+        .find(|(interaction, _)| **interaction == Interaction::Pressed)
+        .map(|(_, button)| *button);
+
+    match pressed_key.or(clicked) {
+        Some(GameOverButton::Retry) => next_state.set(AppState::Restarting),
+        Some(GameOverButton::RetrySameSeed) => {
+            pending_seed.0 = Some(seed.0);
+            next_state.set(AppState::Restarting);
+        }
+        Some(GameOverButton::Menu) => next_state.set(AppState::Menu),
+        None => {}
+    }
+}
+
+#[derive(Debug, Component)]
+pub struct LoadingMarker;
+
+fn spawn_loading_screen(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Loading UI"),
+        LoadingMarker,
+        Text::new("Loading..."),
+    ));
+}
+
+fn despawn_loading_screen(mut commands: Commands, query: Query<Entity, With<LoadingMarker>>) {
+    let Ok(loading_screen) = query.get_single() else {
+        return;
+    };
+
+    commands
+        .get_entity(loading_screen)
+        .expect("Tried to despawn an invalid entity")
+        .despawn_recursive();
+}
+
+fn spawn_start_menu(mut commands: Commands, sprites: Res<SpriteAssets>) {
+    let background = Sprite {
+        image: sprites.start_screen_background.clone(),
+        custom_size: Some(Vec2::new(WINDOW_WIDTH, WINDOW_HEIGHT)),
+        ..default()
+    };
+
+    commands.spawn((
+        Name::new("Startup background"),
+        background,
+        Transform::from_translation(Vec3::new(0.0, 0.0, 0.0)),
+        StateScoped(AppState::Menu),
+    ));
+
+    let ui_padding = 20.0;
+
+    let start_screen_instructions = Sprite {
+        image: sprites.start_screen_instructions.clone(),
+        custom_size: Some(Vec2::new(
+            WINDOW_WIDTH - ui_padding * 5.0,
+            WINDOW_HEIGHT - ui_padding * 5.0,
+        )),
+        ..default()
+    };
+
+    commands.spawn((
+        Name::new("Start game UI"),
+        start_screen_instructions,
+        Transform::from_translation(Vec3::new(0.0, 0.0, 1.0)),
+        StateScoped(AppState::Menu),
+    ));
+}
+
+fn start_game_on_input(
+    mut input: EventReader<input::ButtonPressed>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    for _ in input.read() {
+        next_state.set(AppState::InGame);
+    }
+
+    input.clear();
+}
+
+/// Half the thickness of the ground and ceiling colliders.
+pub const GROUND_HALF_HEIGHT: f32 = 10.0;
+
+fn spawn_ground_and_ceiling(
+    mut commands: Commands,
+    playfield: Res<Playfield>,
+    sprites: Res<SpriteAssets>,
+) {
+    let (width, height) = (playfield.width, playfield.height);
+    let background = Sprite::from_image(sprites.background_day.clone());
+    let bottom = Sprite::from_image(sprites.ground.clone());
+
+    commands.spawn((
+        Name::new("Background image"),
+        background,
+        Transform::from_xyz(0.0, 0.0, -2.0).with_scale(Vec3::new(2.25, 2.25, 1.0)),
+        StateScoped(AppState::InGame),
+    ));
+
+    commands.spawn((
+        Name::new("Ground texture"),
+        bottom,
+        Transform::from_xyz(0.0, -500.0, -1.0).with_scale(Vec3::new(2.25, 2.25, 1.0)),
+        StateScoped(AppState::InGame),
+    ));
+
+    commands.spawn((
+        Name::new("Ceiling collider"),
+        Hazard::Ceiling,
+        Collider::cuboid(width, GROUND_HALF_HEIGHT),
+        Hazard::Ceiling.collision_groups(),
+        Transform::from_xyz(-width / 2.0, height / 2.0, 0.0),
+        RigidBody::Fixed,
+        StateScoped(AppState::InGame),
+    ));
+
+    commands.spawn((
+        Name::new("Ground collider"),
+        Hazard::Ground,
+        Collider::cuboid(width, GROUND_HALF_HEIGHT),
+        Hazard::Ground.collision_groups(),
+        Transform::from_xyz(-width / 2.0, -height / 2.0, 0.0),
+        RigidBody::Fixed,
+        StateScoped(AppState::InGame),
+    ));
+}
+
+/// Points for every obstacle passed.
+pub const OBSTACLE_POINTS: usize = 100;
+
+#[derive(Default, Resource)]
+pub struct Score {
+    score: usize,
+    obstacles_passed: usize,
+}
+
+impl Score {
+    pub fn passed_obstactle(&mut self) {
+        self.score += OBSTACLE_POINTS;
+        self.obstacles_passed += 1;
+    }
+
+    pub fn add_bonus(&mut self, points: usize) {
+        self.score += points;
+    }
+
+    pub fn reset(&mut self) {
+        self.score = 0;
+        self.obstacles_passed = 0;
+    }
+
+    pub fn value(&self) -> usize {
+        self.score
+    }
+
+    /// Obstacles passed this run, not counting any bonus points.
+    pub fn obstacles_passed(&self) -> usize {
+        self.obstacles_passed
+    }
+}
+
+#[derive(Default, Component)]
+pub struct ScoreMarker;
+
+#[derive(Default, Component)]
+pub struct SeedMarker;
+
+fn spawn_ui(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Seed UI"),
+        SeedMarker,
+        Text::default(),
+        TextFont::from_font_size(16.0),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(15.0),
+            left: Val::Px(15.0),
+            ..default()
+        },
+        StateScoped(AppState::InGame),
+    ));
+
+    commands.spawn((
+        Name::new("Score UI"),
+        ScoreMarker,
+        Text::new("0"),
+        TextLayout::new_with_justify(JustifyText::Right),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(15.0),
+            right: Val::Px(15.0),
+            ..default()
+        },
+        StateScoped(AppState::InGame),
+    ));
+}
+
+fn update_score(score: Res<Score>, mut score_display: Query<&mut Text, With<ScoreMarker>>) {
+    let Ok(mut score_display) = score_display.get_single_mut() else {
+        return;
+    };
+
+    score_display.0 = format!("{}", score.score);
+}
+
+fn update_seed(seed: Res<RunSeed>, mut seed_display: Query<&mut Text, With<SeedMarker>>) {
+    let Ok(mut seed_display) = seed_display.get_single_mut() else {
+        return;
+    };
+
+    seed_display.0 = format!("Seed {}", seed.0);
+}
+
+pub fn player_scored(
+    mut commands: Commands,
+    mut passed_obstacle: EventReader<crate::obstacles::PlayerPassedObstacle>,
+    mut score: ResMut<Score>,
+    audio: Res<AudioAssets>,
+) {
+    for _ in passed_obstacle.read() {
+        score.passed_obstactle();
+
+        commands.spawn((
+            Name::new("Point scored audio"),
+            PlaybackSettings::DESPAWN.with_volume(Volume::new(0.1)),
+            AudioPlayer::new(audio.point.clone()),
+        ));
+    }
+}
+
+fn end_run_on_hit(
+    mut commands: Commands,
+    mut hits: BirdHits,
+    birds: Query<(), With<PlayerControlled>>,
+    ceiling_rule: Res<CeilingRule>,
+    mut death_causes: ResMut<DeathCauses>,
+    mut next_state: ResMut<NextState<GameState>>,
+    audio_assets: Res<AudioAssets>,
+) {
+    let mut fatal_hits: Vec<(Entity, Hazard)> = Vec::new();
+
+    for (bird, hazard) in hits.read() {
+        commands.spawn(hit_sound(hazard, &audio_assets));
+
+        let fatal = hazard != Hazard::Ceiling || *ceiling_rule == CeilingRule::Deadly;
+        if fatal && !fatal_hits.iter().any(|(hit, _)| *hit == bird) {
+            fatal_hits.push((bird, hazard));
+        }
+    }
+
+    let Some(&(_, first_hazard)) = fatal_hits.first() else {
+        return;
+    };
+
+    // The run ends with the last bird, which falls to the ground before the
+    // game over screen. Any others drop out of the round as they're hit.
+    if fatal_hits.len() >= birds.iter().count() {
+        death_causes.record(first_hazard);
+        next_state.set(GameState::Dying);
+        return;
+    }
+
+    for (bird, hazard) in fatal_hits {
+        death_causes.record(hazard);
+        commands.entity(bird).despawn_recursive();
+    }
+}
+
+/// The same hit sample, pitched down into a thud for the ground and up into
+/// a light bump for the ceiling.
+fn hit_sound(hazard: Hazard, audio_assets: &AudioAssets) -> impl Bundle {
+    let (volume, speed) = match hazard {
+        Hazard::Pipe => (0.25, 1.0),
+        Hazard::Ground => (0.3, 0.7),
+        Hazard::Ceiling => (0.15, 1.5),
+    };
+
+    (
+        Name::new("Hit effect"),
+        AudioPlayer(audio_assets.hit.clone()),
+        PlaybackSettings::DESPAWN
+            .with_volume(Volume::new(volume))
+            .with_speed(speed),
+    )
+}
@@ -0,0 +1,586 @@
+//! Procedural gap placement that never asks the impossible of the bird.
+//!
+//! Gap sizes and heights vary from pipe to pipe, but each new gap is kept
+//! within the [`FlightEnvelope`] of the previous one: no higher than the
+//! bird can climb, and no lower than it can fall, in the time between them.
+//! Obstacles that move are kept far enough inside those limits that they
+//! stay reachable wherever they move to. [`FlightEnvelope::is_passable`]
+//! checks a whole sequence against the same limits, and [`plan_run`]
+//! produces sequences to check without running the game.
+
+use crate::{
+    bird::{BIRD_RADIUS, BIRD_SCALE},
+    config::GameConfig,
+    difficulty::{Difficulty, Pacing},
+    game::OBSTACLE_POINTS,
+    motion::{ObstacleKind, SHIFT_DISTANCE},
+    obstacles::GAP_OFFSET,
+    physics::PIXELS_PER_METER,
+    *,
+};
+use rand::Rng;
+use rand_core::SeedableRng;
+
+/// Acceleration due to gravity before the bird's gravity scale, in world
+/// units per second squared.
+pub const STANDARD_GRAVITY: f32 = 9.81 * PIXELS_PER_METER;
+
+/// How far a gap's size may stray either side of the difficulty curve's.
+const GAP_SIZE_JITTER: f32 = 0.15;
+/// Share of the bird's reach a new gap may ask for, leaving room for
+/// imperfect timing.
+const REACH_MARGIN: f32 = 0.8;
+/// Room left above and below the bird in the smallest allowed gap.
+const GAP_CLEARANCE: f32 = 12.0;
+
+/// A gap chosen by the generator.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlannedGap {
+    /// World-space height of the middle of the gap.
+    pub center: f32,
+    pub size: f32,
+    /// Seconds between this gap and the one before it.
+    pub interval: f32,
+    pub pipe_speed: f32,
+    pub kind: ObstacleKind,
+}
+
+/// How far a bird can move vertically, given the game's tuning.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FlightEnvelope {
+    /// Average climb speed when flapping once at the top of every arc.
+    pub climb_speed: f32,
+    /// Downward acceleration on the bird, as a positive number.
+    pub gravity: f32,
+    pub bird_radius: f32,
+    /// Half the width of a pipe.
+    pub obstacle_width: f32,
+}
+
+impl FlightEnvelope {
+    pub fn from_config(config: &GameConfig) -> Self {
+        Self {
+            climb_speed: config.jump_velocity / 2.0,
+            gravity: STANDARD_GRAVITY * config.gravity_scale,
+            bird_radius: BIRD_RADIUS * BIRD_SCALE,
+            obstacle_width: config.obstacle_width,
+        }
+    }
+
+    /// Highest the bird can climb in `seconds`.
+    pub fn max_rise(&self, seconds: f32) -> f32 {
+        self.climb_speed * seconds
+    }
+
+    /// Furthest the bird falls in `seconds` from a standstill.
+    pub fn max_drop(&self, seconds: f32) -> f32 {
+        0.5 * self.gravity * seconds * seconds
+    }
+
+    /// Seconds the bird spends overlapping a pipe at `pipe_speed`.
+    pub fn crossing_time(&self, pipe_speed: f32) -> f32 {
+        2.0 * (self.obstacle_width + self.bird_radius) / pipe_speed
+    }
+
+    /// The smallest gap the bird fits through at `pipe_speed`: its own
+    /// height, plus how far a flap arc sags while crossing the pipe.
+    pub fn min_gap_size(&self, pipe_speed: f32) -> f32 {
+        let crossing = self.crossing_time(pipe_speed);
+
+        2.0 * (self.bird_radius + GAP_CLEARANCE) + self.gravity * crossing * crossing / 8.0
+    }
+
+    /// Whether the bird can get from the middle of `from` to the middle of
+    /// `to` in the time between leaving one and entering the other, however
+    /// far either of them has moved.
+    pub fn can_reach(&self, from: &PlannedGap, to: &PlannedGap) -> bool {
+        let (low, high) = self.reach(from, to.interval, to.pipe_speed, 1.0);
+        let displacement = from.kind.max_displacement() + to.kind.max_displacement();
+
+        low <= to.center - displacement && to.center + displacement <= high
+    }
+
+    /// Whether every gap in `gaps` is big enough and reachable from the one
+    /// before it.
+    pub fn is_passable(&self, gaps: &[PlannedGap]) -> bool {
+        gaps.iter()
+            .all(|gap| gap.size >= self.min_gap_size(gap.pipe_speed))
+            && gaps
+                .windows(2)
+                .all(|pair| self.can_reach(&pair[0], &pair[1]))
+    }
+
+    /// Lowest and highest centre reachable after `from`, using `margin` of
+    /// the bird's full reach.
+    fn reach(&self, from: &PlannedGap, interval: f32, pipe_speed: f32, margin: f32) -> (f32, f32) {
+        let free_time = (interval - self.crossing_time(pipe_speed)).max(0.0);
+
+        (
+            from.center - self.max_drop(free_time) * margin,
+            from.center + self.max_rise(free_time) * margin,
+        )
+    }
+}
+
+/// Places each new gap relative to the last one.
+#[derive(Debug, Default, Resource)]
+pub struct PipeGenerator {
+    previous: Option<PlannedGap>,
+}
+
+impl PipeGenerator {
+    /// Forgets the previous gap, for the start of a new run.
+    pub fn reset(&mut self) {
+        self.previous = None;
+    }
+
+    pub fn next(&mut self, rng: &mut impl Rng, config: &GameConfig, pacing: &Pacing) -> PlannedGap {
+        let envelope = FlightEnvelope::from_config(config);
+
+        let mut kind = if rng.gen_bool(f64::from(pacing.moving_share)) {
+            match rng.gen_range(0..3) {
+                0 => ObstacleKind::Oscillating,
+                1 => ObstacleKind::Breathing,
+                _ => ObstacleKind::Shifting(SHIFT_DISTANCE),
+            }
+        } else {
+            ObstacleKind::Static
+        };
+
+        let size = pacing.gap_size * rng.gen_range(1.0 - GAP_SIZE_JITTER..=1.0 + GAP_SIZE_JITTER);
+        let size = size.max(envelope.min_gap_size(pacing.pipe_speed));
+
+        let middle = (config.gap_height_min + config.gap_height_max) / 2.0 + GAP_OFFSET;
+        let spread =
+            (config.gap_height_max - config.gap_height_min) / 2.0 * pacing.vertical_variance;
+        let mut center = middle + spread * rng.gen_range(-1.0..=1.0);
+
+        if let Some(previous) = &self.previous {
+            let (reach_low, reach_high) = envelope.reach(
+                previous,
+                pacing.spawn_interval,
+                pacing.pipe_speed,
+                REACH_MARGIN,
+            );
+            let room = (reach_high - reach_low) / 2.0;
+
+            // Too tight for this obstacle to move, so keep it still.
+            if previous.kind.max_displacement() + kind.max_displacement() > room {
+                kind = ObstacleKind::Static;
+            }
+
+            let displacement =
+                (previous.kind.max_displacement() + kind.max_displacement()).min(room);
+            let (reach_low, reach_high) = (reach_low + displacement, reach_high - displacement);
+            let (low, high) = (
+                reach_low.max(middle - spread),
+                reach_high.min(middle + spread),
+            );
+
+            // Reachability wins over the configured range if they ever
+            // disagree, such as right after the config changes.
+            center = if low <= high {
+                center.clamp(low, high)
+            } else {
+                center.clamp(reach_low, reach_high)
+            };
+        }
+
+        // Shift towards the middle, so the gap never leaves the screen.
+        if let ObstacleKind::Shifting(distance) = &mut kind {
+            *distance = distance.copysign(middle - center);
+        }
+
+        let gap = PlannedGap {
+            center,
+            size,
+            interval: pacing.spawn_interval,
+            pipe_speed: pacing.pipe_speed,
+            kind,
+        };
+        self.previous = Some(gap);
+
+        gap
+    }
+}
+
+/// The first `count` gaps of a run on `seed`, assuming the bird passes every
+/// pipe as soon as it's spawned. That's a little harder than a real run,
+/// where the score lags a few pipes behind.
+pub fn plan_run(
+    seed: u64,
+    count: usize,
+    config: &GameConfig,
+    difficulty: Difficulty,
+) -> Vec<PlannedGap> {
+    let mut rng = WyRand::from_seed(seed.to_be_bytes());
+    let mut generator = PipeGenerator::default();
+    let curve = difficulty.curve(config);
+
+    (0..count)
+        .map(|passed| {
+            let pacing = curve.pacing(config, passed * OBSTACLE_POINTS);
+            generator.next(&mut rng, config, &pacing)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bird::BirdMarker,
+        game::{GROUND_HALF_HEIGHT, Playfield},
+        headless::tests::{game_state, headless_app, in_game},
+        input::ButtonPressed,
+        obstacles::{Gap, ObstacleMarker, PlayerPassedObstacle},
+    };
+    use bevy_rapier2d::{prelude::*, rapier::dynamics::IntegrationParameters};
+    use std::collections::{HashSet, VecDeque};
+
+    const SEEDS: u64 = 8;
+    /// Enough pipes to reach the end of every difficulty's curve.
+    const PIPES_PER_RUN: usize = 45;
+    const MAX_UPDATES: usize = 20_000;
+
+    /// Ticks the pilot looks ahead, enough to see the next pipe while still
+    /// crossing the last one at the slowest pace.
+    const HORIZON: usize = 160;
+    /// Room the pilot leaves around the bird, for the physics it doesn't
+    /// model exactly.
+    const MARGIN: f32 = 2.0;
+    /// Search steps after which the pilot gives up on finding a way through.
+    const MAX_SEARCH_STEPS: usize = 200_000;
+
+    /// A pipe pair's opening on one tick, in world space.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Opening {
+        left: f32,
+        right: f32,
+        bottom: f32,
+        top: f32,
+    }
+
+    /// Every pipe pair's opening right now, from left to right.
+    fn openings(world: &mut World) -> Vec<Opening> {
+        let width = world.resource::<GameConfig>().obstacle_width;
+        let mut openings: Vec<_> = world
+            .query_filtered::<(&Transform, &Gap), With<ObstacleMarker>>()
+            .iter(world)
+            .map(|(transform, gap)| {
+                let (bottom, top) = gap.bounds(transform.translation.y);
+                Opening {
+                    left: transform.translation.x - width,
+                    right: transform.translation.x + width,
+                    bottom,
+                    top,
+                }
+            })
+            .collect();
+        openings.sort_by(|a, b| a.left.total_cmp(&b.left));
+
+        openings
+    }
+
+    /// One tick of the bird's flight, as far as the pilot is concerned.
+    #[derive(Clone, Copy)]
+    struct Flight {
+        y: f32,
+        velocity: f32,
+    }
+
+    /// How the bird moves from one tick to the next.
+    struct Wings {
+        timestep: f32,
+        gravity: f32,
+        jump_velocity: f32,
+        /// How much higher the bird ends each tick than its new velocity
+        /// alone would put it, as the solver splits every tick into
+        /// substeps.
+        drift: f32,
+    }
+
+    impl Wings {
+        fn from_config(config: &GameConfig) -> Self {
+            let timestep = (1.0 / TICK_RATE_HZ) as f32;
+            let gravity = STANDARD_GRAVITY * config.gravity_scale;
+            let substeps = IntegrationParameters::default().num_solver_iterations.get() as f32;
+
+            Self {
+                timestep,
+                gravity,
+                jump_velocity: config.jump_velocity,
+                drift: gravity * timestep * timestep * (substeps - 1.0) / (2.0 * substeps),
+            }
+        }
+
+        fn fly(&self, flight: Flight, flap: bool) -> Flight {
+            let mut velocity = flight.velocity;
+            if flap && velocity <= self.jump_velocity / 2.0 {
+                velocity = self.jump_velocity;
+            }
+            velocity -= self.gravity * self.timestep;
+
+            Flight {
+                y: flight.y + velocity * self.timestep + self.drift,
+                velocity,
+            }
+        }
+
+        /// How far a flap carries the bird up before it starts to fall.
+        fn rise(&self) -> f32 {
+            self.jump_velocity * self.jump_velocity / (2.0 * self.gravity)
+        }
+    }
+
+    /// The openings the bird has to fit through on each of the coming
+    /// ticks.
+    struct Course {
+        wings: Wings,
+        radius: f32,
+        bird_x: f32,
+        floor: f32,
+        ceiling: f32,
+        openings: Vec<Vec<Opening>>,
+        /// Height to top each of the bird's arcs out at, per tick.
+        aim: Vec<f32>,
+    }
+
+    impl Course {
+        /// The course over the next [`HORIZON`] ticks of `timeline`.
+        fn new(world: &World, timeline: &[Vec<Opening>]) -> Self {
+            let config = world.resource::<GameConfig>();
+            let playfield = world.resource::<Playfield>();
+            let radius = BIRD_RADIUS * BIRD_SCALE;
+            let openings: Vec<_> = (0..=HORIZON)
+                .map(|tick| timeline.get(tick).cloned().unwrap_or_default())
+                .collect();
+
+            // Start from the middle of the playfield, then line up with each
+            // pipe in turn as it comes up.
+            let mut height = 0.0;
+            let aim = openings
+                .iter()
+                .map(|openings| {
+                    if let Some(next) = openings
+                        .iter()
+                        .find(|opening| opening.right + radius > config.birb_x)
+                    {
+                        height = (next.bottom + next.top) / 2.0;
+                    }
+                    height
+                })
+                .collect();
+
+            Self {
+                wings: Wings::from_config(config),
+                radius,
+                bird_x: config.birb_x,
+                floor: -playfield.height / 2.0 + GROUND_HALF_HEIGHT,
+                ceiling: playfield.height / 2.0 - GROUND_HALF_HEIGHT,
+                openings,
+                aim,
+            }
+        }
+
+        fn is_clear(&self, tick: usize, y: f32) -> bool {
+            let radius = self.radius + MARGIN;
+            if y - radius < self.floor || y + radius > self.ceiling {
+                return false;
+            }
+
+            self.openings[tick].iter().all(|opening| {
+                let across = (opening.left - self.bird_x)
+                    .max(self.bird_x - opening.right)
+                    .max(0.0);
+                if across >= radius {
+                    return true;
+                }
+
+                let reach = (radius * radius - across * across).sqrt();
+                y - reach >= opening.bottom && y + reach <= opening.top
+            })
+        }
+
+        /// Depth-first search for flaps from `tick` to the horizon.
+        fn search(
+            &self,
+            tick: usize,
+            flight: Flight,
+            plan: &mut Vec<bool>,
+            dead_ends: &mut HashSet<(usize, i32, i32)>,
+            steps: &mut usize,
+        ) -> bool {
+            if tick == HORIZON {
+                return true;
+            }
+
+            let key = (
+                tick,
+                (flight.y * 2.0).round() as i32,
+                flight.velocity.round() as i32,
+            );
+            *steps += 1;
+            if *steps > MAX_SEARCH_STEPS || dead_ends.contains(&key) {
+                return false;
+            }
+
+            let choices: &[bool] = if flight.velocity > self.wings.jump_velocity / 2.0 {
+                &[false]
+            } else if flight.y + self.wings.rise() < self.aim[tick] {
+                &[true, false]
+            } else {
+                &[false, true]
+            };
+
+            for &flap in choices {
+                let next = self.wings.fly(flight, flap);
+                if self.is_clear(tick + 1, next.y) {
+                    plan.push(flap);
+                    if self.search(tick + 1, next, plan, dead_ends, steps) {
+                        return true;
+                    }
+                    plan.pop();
+                }
+            }
+
+            dead_ends.insert(key);
+            false
+        }
+    }
+
+    /// A scripted player that searches for flaps that clear every pipe in
+    /// the run. It knows where each pipe will be from a scouting run, as the
+    /// generator assumes the bird can head for a gap as soon as it has left
+    /// the last one, so a run only fails when the generator asks the
+    /// impossible rather than when a simple bot guesses wrong.
+    #[derive(Default)]
+    struct Pilot {
+        plan: VecDeque<bool>,
+        /// Where the plan puts the bird ahead of the next tick.
+        expected: Option<Flight>,
+    }
+
+    impl Pilot {
+        /// Whether to flap ahead of the next tick, `timeline` starting from
+        /// this one.
+        fn should_flap(&mut self, world: &mut World, timeline: &[Vec<Opening>]) -> bool {
+            assert_eq!(
+                timeline.first(),
+                Some(&openings(world)),
+                "the pipes strayed from the scouting run"
+            );
+
+            let (bird, velocity) = world
+                .query_filtered::<(&Transform, &Velocity), With<BirdMarker>>()
+                .single(world);
+            let flight = Flight {
+                y: bird.translation.y,
+                velocity: velocity.linvel.y,
+            };
+            let deviated = self.expected.is_some_and(|expected| {
+                (expected.y - flight.y).abs() > 0.5
+                    || (expected.velocity - flight.velocity).abs() > 1.0
+            });
+
+            if self.plan.len() < HORIZON / 2 || deviated {
+                let course = Course::new(world, timeline);
+                let mut plan = Vec::with_capacity(HORIZON);
+                let mut steps = 0;
+                if course.search(0, flight, &mut plan, &mut HashSet::new(), &mut steps) {
+                    self.plan = plan.into();
+                }
+            }
+
+            let flap = self.plan.pop_front().unwrap_or(false);
+            let wings = Wings::from_config(world.resource::<GameConfig>());
+            self.expected = Some(wings.fly(flight, flap));
+            flap
+        }
+    }
+
+    /// Holds the bird in the middle of the next gap, or of the playfield
+    /// before the first pipe, so it can't crash.
+    fn hold_bird_in_next_gap(world: &mut World, openings: &[Opening]) {
+        let bird_x = world.resource::<GameConfig>().birb_x;
+        let radius = BIRD_RADIUS * BIRD_SCALE;
+        let height = openings
+            .iter()
+            .find(|opening| opening.right + radius > bird_x)
+            .map_or(0.0, |next| (next.bottom + next.top) / 2.0);
+
+        let (mut transform, mut velocity) = world
+            .query_filtered::<(&mut Transform, &mut Velocity), With<BirdMarker>>()
+            .single_mut(world);
+        transform.translation.y = height;
+        *velocity = Velocity::zero();
+    }
+
+    /// Plays `seed` on `difficulty` until the bird has passed
+    /// [`PIPES_PER_RUN`] pipes, calling `before_update` with the update's
+    /// number ahead of each one the run is going on for, and fails if the
+    /// bird dies first.
+    fn fly_run(
+        seed: u64,
+        difficulty: Difficulty,
+        mut before_update: impl FnMut(&mut World, usize),
+    ) {
+        let mut application = headless_app(seed, |application| {
+            application.insert_resource(difficulty);
+        });
+        let mut passed_events = application
+            .world()
+            .resource::<Events<PlayerPassedObstacle>>()
+            .get_cursor();
+        let mut passed = 0;
+
+        for update in 0..MAX_UPDATES {
+            if in_game(&application) && game_state(&application) == Some(GameState::Running) {
+                before_update(application.world_mut(), update);
+            }
+            application.update();
+
+            let events = application
+                .world()
+                .resource::<Events<PlayerPassedObstacle>>();
+            passed += passed_events.read(events).count();
+            if passed >= PIPES_PER_RUN {
+                return;
+            }
+
+            assert!(
+                !matches!(
+                    game_state(&application),
+                    Some(GameState::Dying | GameState::GameOver)
+                ),
+                "seed {seed} on {difficulty:?} crashed after {passed} pipes"
+            );
+        }
+
+        panic!("seed {seed} on {difficulty:?} passed only {passed} pipes in {MAX_UPDATES} updates");
+    }
+
+    #[test]
+    fn every_pipe_can_be_flown_through_on_every_difficulty() {
+        for difficulty in Difficulty::ALL {
+            for seed in 0..SEEDS {
+                // Where every opening is on each update, from a run the bird
+                // can't crash in. The pipes don't depend on how it flies.
+                let mut timeline = Vec::new();
+                fly_run(seed, difficulty, |world, update| {
+                    let openings = openings(world);
+                    hold_bird_in_next_gap(world, &openings);
+                    timeline.resize_with(update, Vec::new);
+                    timeline.push(openings);
+                });
+
+                let mut pilot = Pilot::default();
+                fly_run(seed, difficulty, |world, update| {
+                    if pilot.should_flap(world, &timeline[update..]) {
+                        world.send_event(ButtonPressed::default());
+                    }
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,290 @@
+//! Ghost runs: the player's best run on a seed, flown again alongside them.
+//!
+//! Every endless run's button presses are recorded, and the best run for
+//! each seed and difficulty is kept. Starting a run on a seed with a saved
+//! run spawns a see-through ghost bird that repeats those presses. The
+//! ghost has a collider only so it weighs the same as the real bird; it
+//! belongs to no collision group, so it flies through pipes and birds alike
+//! and simply vanishes at the tick the original run ended.
+
+use crate::{
+    autopilot::Autopilot,
+    bird::{BirdAtlas, BirdMarker, flap, spawn_bird},
+    config::GameConfig,
+    difficulty::Difficulty,
+    game::{GameMode, PendingSeed, RunSeed, RunTick, Score},
+    input::ButtonPressed,
+    levels::ActiveLevel,
+    multiplayer::single_player,
+    *,
+};
+use bevy_rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Save format of [`GhostRuns`]; see [`storage`].
+pub const GHOST_RUNS_VERSION: u32 = 1;
+
+/// How many seeds' best runs are kept, dropping the lowest scores first.
+pub const MAX_GHOST_RUNS: usize = 20;
+
+const STORAGE_KEY: &str = "ghosts";
+
+const GHOST_TINT: Color = Color::srgba(1.0, 1.0, 1.0, 0.4);
+
+pub struct GhostPlugin;
+
+impl Plugin for GhostPlugin {
+    fn build(&self, application: &mut App) {
+        application
+            .insert_resource(GhostRuns::load())
+            .init_resource::<GhostRecorder>()
+            .add_systems(OnEnter(AppState::Menu), spawn_ghost_hint)
+            .add_systems(Update, race_personal_best.run_if(in_state(AppState::Menu)))
+            .add_systems(
+                OnEnter(AppState::InGame),
+                (start_recording, spawn_ghost)
+                    .chain()
+                    .after(obstacles::seed_rng)
+                    .run_if(ghost_eligible),
+            )
+            .add_systems(
+                FixedUpdate,
+                (record_input, fly_ghost)
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(in_state(GameState::Running)),
+            )
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                save_ghost_run.run_if(ghost_eligible),
+            );
+    }
+}
+
+/// A recorded run, as flown by the ghost.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GhostRun {
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    pub score: usize,
+    /// The fixed tick the run ended on.
+    pub ticks: u64,
+    /// The fixed tick of every button press, in order.
+    pub inputs: Vec<u64>,
+}
+
+/// The best run for each seed and difficulty, persisted between sessions.
+#[derive(Debug, Clone, Resource, Serialize, Deserialize)]
+pub struct GhostRuns {
+    pub version: u32,
+    /// Best first.
+    pub runs: Vec<GhostRun>,
+}
+
+impl Default for GhostRuns {
+    fn default() -> Self {
+        Self {
+            version: GHOST_RUNS_VERSION,
+            runs: Vec::new(),
+        }
+    }
+}
+
+impl GhostRuns {
+    /// Reads the saved runs, starting afresh if there aren't readable ones.
+    pub fn load() -> Self {
+        storage::load_ron(STORAGE_KEY, GHOST_RUNS_VERSION).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        storage::save_ron(STORAGE_KEY, self);
+    }
+
+    pub fn get(&self, seed: u64, difficulty: Difficulty) -> Option<&GhostRun> {
+        self.runs
+            .iter()
+            .find(|run| run.seed == seed && run.difficulty == difficulty)
+    }
+
+    /// The best run on any seed at `difficulty`.
+    pub fn personal_best(&self, difficulty: Difficulty) -> Option<&GhostRun> {
+        self.runs.iter().find(|run| run.difficulty == difficulty)
+    }
+
+    /// Keeps `run` if it beats the saved run on its seed, returning whether
+    /// it did.
+    pub fn submit(&mut self, run: GhostRun) -> bool {
+        if self
+            .get(run.seed, run.difficulty)
+            .is_some_and(|existing| existing.score >= run.score)
+        {
+            return false;
+        }
+
+        self.runs
+            .retain(|existing| existing.seed != run.seed || existing.difficulty != run.difficulty);
+
+        let index = self
+            .runs
+            .iter()
+            .position(|existing| existing.score < run.score)
+            .unwrap_or(self.runs.len());
+        self.runs.insert(index, run);
+        self.runs.truncate(MAX_GHOST_RUNS);
+
+        true
+    }
+}
+
+/// Presses made so far in the current run.
+#[derive(Debug, Default, Resource)]
+struct GhostRecorder {
+    inputs: Vec<u64>,
+}
+
+/// A bird repeating a [`GhostRun`].
+#[derive(Debug, Component)]
+pub struct GhostBird {
+    inputs: Vec<u64>,
+    next_input: usize,
+    ticks: u64,
+}
+
+/// Only solo endless and daily runs flown by a person are recorded or raced
+/// against.
+fn ghost_eligible(
+    mode: Res<GameMode>,
+    level: Option<Res<ActiveLevel>>,
+    autopilot: Option<Res<Autopilot>>,
+    players: Res<multiplayer::PlayerCount>,
+) -> bool {
+    matches!(*mode, GameMode::Endless | GameMode::Daily)
+        && level.is_none()
+        && !autopilot.is_some_and(|autopilot| autopilot.enabled)
+        && single_player(players)
+}
+
+fn start_recording(mut recorder: ResMut<GhostRecorder>) {
+    recorder.inputs.clear();
+}
+
+fn record_input(
+    mut input_pressed: EventReader<ButtonPressed>,
+    mut recorder: ResMut<GhostRecorder>,
+    tick: Res<RunTick>,
+) {
+    for pressed in input_pressed.read() {
+        if pressed.player == 0 {
+            recorder.inputs.push(tick.0);
+        }
+    }
+}
+
+fn spawn_ghost(
+    mut commands: Commands,
+    ghosts: Res<GhostRuns>,
+    seed: Res<RunSeed>,
+    difficulty: Res<Difficulty>,
+    assets: Res<SpriteAssets>,
+    atlas: Res<BirdAtlas>,
+    config: Res<GameConfig>,
+) {
+    let Some(run) = ghosts.get(seed.0, *difficulty) else {
+        return;
+    };
+
+    let ghost = spawn_bird(&mut commands, &assets, &atlas, &config, GHOST_TINT);
+    commands
+        .entity(ghost)
+        .remove::<(BirdMarker, ActiveEvents)>()
+        .insert((
+            Name::new("Ghost birb"),
+            GhostBird {
+                inputs: run.inputs.clone(),
+                next_input: 0,
+                ticks: run.ticks,
+            },
+            CollisionGroups::new(Group::NONE, Group::NONE),
+        ));
+}
+
+fn fly_ghost(
+    mut commands: Commands,
+    mut ghosts: Query<(Entity, &mut GhostBird, &mut Velocity)>,
+    tick: Res<RunTick>,
+    config: Res<GameConfig>,
+) {
+    for (entity, mut ghost, mut velocity) in ghosts.iter_mut() {
+        if tick.0 >= ghost.ticks {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        while ghost
+            .inputs
+            .get(ghost.next_input)
+            .is_some_and(|input| *input <= tick.0)
+        {
+            flap(&mut velocity, &config);
+            ghost.next_input += 1;
+        }
+    }
+}
+
+fn save_ghost_run(
+    mut ghosts: ResMut<GhostRuns>,
+    recorder: Res<GhostRecorder>,
+    seed: Res<RunSeed>,
+    difficulty: Res<Difficulty>,
+    score: Res<Score>,
+    tick: Res<RunTick>,
+) {
+    if score.value() == 0 {
+        return;
+    }
+
+    let run = GhostRun {
+        seed: seed.0,
+        difficulty: *difficulty,
+        score: score.value(),
+        ticks: tick.0,
+        inputs: recorder.inputs.clone(),
+    };
+
+    if ghosts.submit(run) {
+        ghosts.save();
+    }
+}
+
+fn spawn_ghost_hint(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Ghost hint"),
+        Text::new("G: Race your best"),
+        TextFont::from_font_size(18.0),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(40.0),
+            right: Val::Px(15.0),
+            ..default()
+        },
+        StateScoped(AppState::Menu),
+    ));
+}
+
+/// Starts a run on the seed of the best run at the current difficulty, so
+/// its ghost flies alongside.
+fn race_personal_best(
+    keys: Res<ButtonInput<KeyCode>>,
+    ghosts: Res<GhostRuns>,
+    difficulty: Res<Difficulty>,
+    mut pending_seed: ResMut<PendingSeed>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if !keys.just_pressed(KeyCode::KeyG) {
+        return;
+    }
+
+    if let Some(best) = ghosts.personal_best(*difficulty) {
+        pending_seed.0 = Some(best.seed);
+        next_state.set(AppState::InGame);
+    }
+}
@@ -0,0 +1,341 @@
+//! A gym-style environment for training agents on birb.
+//!
+//! [`BirbEnv`] drives a headless app one fixed tick per [`BirbEnv::step`].
+//! [`serve`] exposes the same API as a JSON-lines protocol, one request per
+//! line:
+//!
+//! ```text
+//! {"cmd": "reset", "seed": 42}     -> {"observation": {...}}
+//! {"cmd": "step", "action": "flap"} -> {"observation": {...}, "reward": 0.01, "done": false}
+//! {"cmd": "close"}
+//! ```
+
+use crate::{
+    BirbPlugins,
+    bird::{BIRD_RADIUS, BIRD_SCALE, BirdMarker},
+    config::GameConfig,
+    daily::DailyBestsPlugin,
+    game::{PendingSeed, RunTick, Score},
+    ghost::GhostPlugin,
+    headless::HeadlessPlugin,
+    highscores::HighScoresPlugin,
+    input::ButtonPressed,
+    levels::LevelRecordsPlugin,
+    obstacles::{Gap, ObstacleNotScored, PlayerPassedObstacle},
+    *,
+};
+use bevy::ecs::event::EventCursor;
+use bevy_rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpListener, ToSocketAddrs},
+};
+
+/// How many upcoming gaps are included in each observation.
+pub const OBSERVED_GAPS: usize = 2;
+
+/// Reward for every tick the bird stays alive.
+const SURVIVAL_REWARD: f32 = 0.01;
+/// Reward for every pipe passed.
+const PASS_REWARD: f32 = 1.0;
+/// Reward for dying.
+const DEATH_REWARD: f32 = -1.0;
+
+/// Upper bound on frames spent waiting for a new run to start.
+const MAX_RESET_UPDATES: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Idle,
+    Flap,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Observation {
+    pub tick: u64,
+    pub score: usize,
+    pub bird_y: f32,
+    pub bird_velocity_y: f32,
+    /// The next gaps ahead of the bird, nearest first.
+    pub gaps: Vec<GapObservation>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct GapObservation {
+    /// Horizontal distance from the bird to the pipe pair.
+    pub distance: f32,
+    pub bottom: f32,
+    pub top: f32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Step {
+    pub observation: Observation,
+    pub reward: f32,
+    pub done: bool,
+}
+
+/// Returned by [`BirbEnv::reset`] when the new run never got going.
+#[derive(Debug)]
+pub struct ResetError;
+
+impl fmt::Display for ResetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the run didn't start within {MAX_RESET_UPDATES} updates")
+    }
+}
+
+impl std::error::Error for ResetError {}
+
+/// A headless birb world advanced one fixed tick at a time.
+pub struct BirbEnv {
+    app: App,
+    /// Counts pipes passed, rather than points, which pickups add to too.
+    passed: EventCursor<PlayerPassedObstacle>,
+    done: bool,
+}
+
+impl Default for BirbEnv {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BirbEnv {
+    pub fn new() -> Self {
+        let mut app = App::new();
+        app.add_plugins(HeadlessPlugin {
+            logging: false,
+            exit_on_game_over: false,
+        })
+        .add_plugins(
+            BirbPlugins::default()
+                .build()
+                .disable::<HighScoresPlugin>()
+                .disable::<LevelRecordsPlugin>()
+                .disable::<DailyBestsPlugin>()
+                .disable::<GhostPlugin>(),
+        );
+        app.finish();
+        app.cleanup();
+
+        Self {
+            app,
+            passed: EventCursor::default(),
+            done: true,
+        }
+    }
+
+    /// Starts a new run on the given seed, or on the next seed in sequence.
+    pub fn reset(&mut self, seed: Option<u64>) -> Result<Observation, ResetError> {
+        self.app.world_mut().resource_mut::<PendingSeed>().0 = seed;
+
+        if self.app_state() == Some(AppState::InGame) {
+            self.app
+                .world_mut()
+                .resource_mut::<NextState<AppState>>()
+                .set(AppState::Restarting);
+        }
+
+        self.done = true;
+        for _ in 0..MAX_RESET_UPDATES {
+            self.app.update();
+
+            if self.app_state() == Some(AppState::InGame) && self.game_state() == GameState::Running
+            {
+                self.done = false;
+                break;
+            }
+        }
+
+        if self.done {
+            return Err(ResetError);
+        }
+
+        self.passed = self
+            .app
+            .world()
+            .resource::<Events<PlayerPassedObstacle>>()
+            .get_cursor_current();
+        Ok(self.observe())
+    }
+
+    /// Applies an action and simulates a single fixed tick.
+    ///
+    /// Stepping a finished run does nothing until [`BirbEnv::reset`].
+    pub fn step(&mut self, action: Action) -> Step {
+        if self.done {
+            return Step {
+                observation: self.observe(),
+                reward: 0.0,
+                done: true,
+            };
+        }
+
+        if action == Action::Flap {
+            self.app.world_mut().send_event(ButtonPressed::default());
+        }
+        self.app.update();
+
+        let pipes_passed = self
+            .passed
+            .read(self.app.world().resource::<Events<PlayerPassedObstacle>>())
+            .count();
+        // The run is decided at the hit; the fall that follows doesn't matter.
+        self.done = matches!(self.game_state(), GameState::Dying | GameState::GameOver);
+
+        let reward = if self.done {
+            DEATH_REWARD
+        } else {
+            SURVIVAL_REWARD + PASS_REWARD * pipes_passed as f32
+        };
+
+        Step {
+            observation: self.observe(),
+            reward,
+            done: self.done,
+        }
+    }
+
+    /// Direct access to the simulated world, for anything the observation
+    /// doesn't cover.
+    pub fn world(&self) -> &World {
+        self.app.world()
+    }
+
+    fn app_state(&self) -> Option<AppState> {
+        self.app
+            .world()
+            .get_resource::<State<AppState>>()
+            .map(|state| *state.get())
+    }
+
+    fn game_state(&self) -> GameState {
+        *self.app.world().resource::<State<GameState>>().get()
+    }
+
+    fn observe(&mut self) -> Observation {
+        let world = self.app.world_mut();
+        let bird_left = {
+            let config = world.resource::<GameConfig>();
+            config.birb_x - BIRD_RADIUS * BIRD_SCALE
+        };
+        let obstacle_width = world.resource::<GameConfig>().obstacle_width;
+
+        let (bird_x, bird_y, bird_velocity_y) = world
+            .query_filtered::<(&Transform, &Velocity), With<BirdMarker>>()
+            .iter(world)
+            .next()
+            .map(|(transform, velocity)| {
+                (
+                    transform.translation.x,
+                    transform.translation.y,
+                    velocity.linvel.y,
+                )
+            })
+            .unwrap_or_default();
+
+        let mut gaps: Vec<GapObservation> = world
+            .query_filtered::<(&Transform, &Gap), ObstacleNotScored>()
+            .iter(world)
+            .filter(|(transform, _)| transform.translation.x + obstacle_width >= bird_left)
+            .map(|(transform, gap)| {
+                let (bottom, top) = gap.bounds(transform.translation.y);
+                GapObservation {
+                    distance: transform.translation.x - bird_x,
+                    bottom,
+                    top,
+                }
+            })
+            .collect();
+        gaps.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        gaps.truncate(OBSERVED_GAPS);
+
+        Observation {
+            tick: world.resource::<RunTick>().0,
+            score: world.resource::<Score>().value(),
+            bird_y,
+            bird_velocity_y,
+            gaps,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+enum Request {
+    Reset {
+        #[serde(default)]
+        seed: Option<u64>,
+    },
+    Step {
+        action: Action,
+    },
+    Close,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum Response {
+    Reset { observation: Observation },
+    Step(Step),
+    Error { error: String },
+}
+
+/// Answers JSON-lines requests from `reader` until it closes or sends
+/// `close`.
+pub fn serve(env: &mut BirbEnv, reader: impl BufRead, mut writer: impl Write) -> io::Result<()> {
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(Request::Reset { seed }) => match env.reset(seed) {
+                Ok(observation) => Response::Reset { observation },
+                Err(error) => Response::Error {
+                    error: error.to_string(),
+                },
+            },
+            Ok(Request::Step { action }) => Response::Step(env.step(action)),
+            Ok(Request::Close) => break,
+            Err(error) => Response::Error {
+                error: error.to_string(),
+            },
+        };
+
+        serde_json::to_writer(&mut writer, &response)?;
+        writeln!(writer)?;
+        writer.flush()?;
+    }
+
+    Ok(())
+}
+
+/// Serves the protocol over stdin and stdout.
+pub fn serve_stdio() -> io::Result<()> {
+    let mut env = BirbEnv::new();
+
+    serve(&mut env, io::stdin().lock(), io::stdout().lock())
+}
+
+/// Serves the protocol to one TCP client at a time.
+pub fn serve_tcp(address: impl ToSocketAddrs) -> io::Result<()> {
+    let listener = TcpListener::bind(address)?;
+    let mut env = BirbEnv::new();
+
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let reader = BufReader::new(stream.try_clone()?);
+
+        if let Err(error) = serve(&mut env, reader, stream) {
+            eprintln!("Gym client disconnected: {error}");
+        }
+    }
+
+    Ok(())
+}
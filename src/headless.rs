@@ -0,0 +1,161 @@
+use crate::{game::Score, *};
+use bevy::time::TimeUpdateStrategy;
+use std::time::Duration;
+
+/// Runs the whole game loop without a window, renderer or audio device.
+///
+/// Assets are replaced with empty handles, the menu is skipped, and every
+/// frame advances time by exactly one fixed tick so a run plays out as fast
+/// as the machine allows.
+pub struct HeadlessPlugin {
+    /// Log to the terminal. Turn this off when stdout is used for something
+    /// else, like the gym protocol.
+    pub logging: bool,
+    /// Quit the app once the bird dies, rather than waiting on the game over
+    /// screen.
+    pub exit_on_game_over: bool,
+}
+
+impl Default for HeadlessPlugin {
+    fn default() -> Self {
+        Self {
+            logging: true,
+            exit_on_game_over: true,
+        }
+    }
+}
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, application: &mut App) {
+        application
+            .add_plugins((
+                MinimalPlugins,
+                bevy::state::app::StatesPlugin,
+                bevy::input::InputPlugin,
+                TransformPlugin,
+                HierarchyPlugin,
+                AssetPlugin::default(),
+                bevy::scene::ScenePlugin,
+            ))
+            .init_asset::<Mesh>()
+            .init_asset::<TextureAtlasLayout>()
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+                1.0 / TICK_RATE_HZ,
+            )))
+            .init_resource::<SpriteAssets>()
+            .init_resource::<AudioAssets>()
+            .insert_state(AppState::Menu)
+            .add_systems(OnEnter(AppState::Menu), start_immediately);
+
+        if self.logging {
+            application.add_plugins(bevy::log::LogPlugin::default());
+        }
+
+        if self.exit_on_game_over {
+            application.add_systems(OnEnter(GameState::GameOver), exit_on_game_over);
+        }
+    }
+}
+
+fn start_immediately(mut next_state: ResMut<NextState<AppState>>) {
+    next_state.set(AppState::InGame);
+}
+
+fn exit_on_game_over(score: Res<Score>, mut app_exit: EventWriter<AppExit>) {
+    info!("Headless run finished with score {}", score.value());
+    app_exit.send(AppExit::Success);
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::{
+        BirbPlugins, daily::DailyBestsPlugin, game::RunTick, ghost::GhostPlugin,
+        highscores::HighScoresPlugin, levels::LevelRecordsPlugin,
+    };
+
+    /// Far more updates than any run in these tests should need.
+    const MAX_UPDATES: usize = 10_000;
+
+    /// A headless app that leaves the player's saved records alone, with
+    /// anything else the test needs added by `configure`.
+    pub(crate) fn headless_app(seed: u64, configure: impl FnOnce(&mut App)) -> App {
+        let mut application = App::new();
+        application
+            .add_plugins(HeadlessPlugin {
+                logging: false,
+                exit_on_game_over: false,
+            })
+            .add_plugins(
+                BirbPlugins { seed }
+                    .build()
+                    .disable::<HighScoresPlugin>()
+                    .disable::<LevelRecordsPlugin>()
+                    .disable::<DailyBestsPlugin>()
+                    .disable::<GhostPlugin>(),
+            );
+        configure(&mut application);
+        application.finish();
+        application.cleanup();
+
+        application
+    }
+
+    pub(crate) fn game_state(application: &App) -> Option<GameState> {
+        application
+            .world()
+            .get_resource::<State<GameState>>()
+            .map(|state| *state.get())
+    }
+
+    pub(crate) fn in_game(application: &App) -> bool {
+        application
+            .world()
+            .get_resource::<State<AppState>>()
+            .is_some_and(|state| *state.get() == AppState::InGame)
+    }
+
+    /// Updates until the game over screen, calling `before_update` ahead of
+    /// each one, and returns the tick the bird died on and the final score.
+    pub(crate) fn run_to_game_over(
+        application: &mut App,
+        mut before_update: impl FnMut(&mut App),
+    ) -> (u64, usize) {
+        let mut death_tick = None;
+
+        for _ in 0..MAX_UPDATES {
+            before_update(application);
+            application.update();
+
+            let dead = matches!(
+                game_state(application),
+                Some(GameState::Dying | GameState::GameOver)
+            );
+            if in_game(application) && dead && death_tick.is_none() {
+                death_tick = Some(application.world().resource::<RunTick>().0);
+            }
+
+            if in_game(application) && game_state(application) == Some(GameState::GameOver) {
+                let score = application.world().resource::<Score>().value();
+                return (death_tick.expect("died before game over"), score);
+            }
+        }
+
+        panic!("the run didn't end within {MAX_UPDATES} updates");
+    }
+
+    #[test]
+    fn unattended_runs_end_the_same_way_every_time() {
+        let first = run_to_game_over(&mut headless_app(7, |_| {}), |_| {});
+        let second = run_to_game_over(&mut headless_app(7, |_| {}), |_| {});
+
+        assert_eq!(first, second);
+
+        // Without a single flap the bird drops straight onto the ground well
+        // before reaching the first pipe.
+        let (death_tick, score) = first;
+        assert!(death_tick > 0);
+        assert!(death_tick < 5 * TICK_RATE_HZ as u64);
+        assert_eq!(score, 0);
+    }
+}
@@ -0,0 +1,255 @@
+use crate::{
+    autopilot::Autopilot,
+    date::Date,
+    difficulty::Difficulty,
+    game::{GameMode, RunSeed, Score},
+    levels::ActiveLevel,
+    multiplayer::single_player,
+    *,
+};
+use serde::{Deserialize, Serialize};
+
+/// Save format of [`HighScores`]; see [`storage`].
+pub const HIGH_SCORES_VERSION: u32 = 1;
+
+/// How many scores are kept for each game mode and difficulty.
+pub const MAX_HIGH_SCORES: usize = 10;
+
+const STORAGE_KEY: &str = "highscores";
+
+pub struct HighScoresPlugin;
+
+impl Plugin for HighScoresPlugin {
+    fn build(&self, application: &mut App) {
+        application
+            .insert_resource(HighScores::load())
+            .init_resource::<LatestRank>()
+            .add_systems(OnEnter(AppState::Menu), spawn_high_score_table)
+            .add_systems(
+                Update,
+                update_high_score_table
+                    .run_if(resource_changed::<Difficulty>)
+                    .run_if(in_state(AppState::Menu)),
+            )
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                (record_high_score, spawn_game_over_high_scores)
+                    .chain()
+                    // Levels and daily challenges keep their own records, and
+                    // a shared run isn't any one player's.
+                    .run_if(not(resource_exists::<ActiveLevel>))
+                    .run_if(not(resource_equals(GameMode::Daily)))
+                    .run_if(single_player),
+            );
+    }
+}
+
+/// The best runs for every game mode and difficulty, persisted between
+/// sessions.
+#[derive(Debug, Clone, Resource, Serialize, Deserialize)]
+pub struct HighScores {
+    pub version: u32,
+    pub entries: Vec<HighScore>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HighScore {
+    pub score: usize,
+    pub date: Date,
+    pub seed: u64,
+    pub mode: GameMode,
+    /// Scores saved before difficulties were told apart count as Normal.
+    #[serde(default)]
+    pub difficulty: Difficulty,
+}
+
+impl Default for HighScores {
+    fn default() -> Self {
+        Self {
+            version: HIGH_SCORES_VERSION,
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl HighScores {
+    /// Reads the saved table, starting afresh if there isn't a readable one.
+    pub fn load() -> Self {
+        storage::load_ron(STORAGE_KEY, HIGH_SCORES_VERSION).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        storage::save_ron(STORAGE_KEY, self);
+    }
+
+    /// Scores for a single mode and difficulty, best first.
+    pub fn table(
+        &self,
+        mode: GameMode,
+        difficulty: Difficulty,
+    ) -> impl Iterator<Item = &HighScore> {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.mode == mode && entry.difficulty == difficulty)
+    }
+
+    pub fn best(&self, mode: GameMode, difficulty: Difficulty) -> Option<&HighScore> {
+        self.table(mode, difficulty).next()
+    }
+
+    /// Adds a run to its table, returning its rank if it made the cut.
+    pub fn submit(&mut self, entry: HighScore) -> Option<usize> {
+        let (mode, difficulty) = (entry.mode, entry.difficulty);
+        let rank = self
+            .table(mode, difficulty)
+            .take_while(|existing| existing.score >= entry.score)
+            .count();
+
+        if rank >= MAX_HIGH_SCORES {
+            return None;
+        }
+
+        let index = self
+            .entries
+            .iter()
+            .position(|existing| {
+                existing.mode == mode
+                    && existing.difficulty == difficulty
+                    && existing.score < entry.score
+            })
+            .unwrap_or(self.entries.len());
+        self.entries.insert(index, entry);
+
+        let mut kept = 0;
+        self.entries.retain(|existing| {
+            if existing.mode != mode || existing.difficulty != difficulty {
+                return true;
+            }
+            kept += 1;
+            kept <= MAX_HIGH_SCORES
+        });
+
+        Some(rank)
+    }
+}
+
+/// Where the most recent run placed in the high score table, if it did.
+#[derive(Debug, Default, Resource)]
+pub struct LatestRank(pub Option<usize>);
+
+fn record_high_score(
+    score: Res<Score>,
+    seed: Res<RunSeed>,
+    mode: Res<GameMode>,
+    difficulty: Res<Difficulty>,
+    mut high_scores: ResMut<HighScores>,
+    mut latest_rank: ResMut<LatestRank>,
+    autopilot: Option<Res<Autopilot>>,
+) {
+    latest_rank.0 = None;
+
+    let flown_by_bot = autopilot.is_some_and(|autopilot| autopilot.enabled);
+    if score.value() == 0 || flown_by_bot {
+        return;
+    }
+
+    latest_rank.0 = high_scores.submit(HighScore {
+        score: score.value(),
+        date: Date::today(),
+        seed: seed.0,
+        mode: *mode,
+        difficulty: *difficulty,
+    });
+
+    if latest_rank.0.is_some() {
+        high_scores.save();
+    }
+}
+
+fn format_table(
+    high_scores: &HighScores,
+    mode: GameMode,
+    difficulty: Difficulty,
+    highlight: Option<usize>,
+) -> String {
+    let mut table = format!("{mode} high scores on {difficulty}\n");
+
+    for (rank, entry) in high_scores.table(mode, difficulty).enumerate() {
+        let marker = if Some(rank) == highlight { ">" } else { " " };
+        table.push_str(&format!(
+            "{marker}{:>2}. {:>6}  {}  seed {}\n",
+            rank + 1,
+            entry.score,
+            entry.date,
+            entry.seed
+        ));
+    }
+
+    if high_scores.best(mode, difficulty).is_none() {
+        table.push_str("No scores yet\n");
+    }
+
+    table
+}
+
+#[derive(Debug, Component)]
+struct HighScoreTableMarker;
+
+fn spawn_high_score_table(
+    mut commands: Commands,
+    high_scores: Res<HighScores>,
+    mode: Res<GameMode>,
+    difficulty: Res<Difficulty>,
+) {
+    commands.spawn((
+        Name::new("High score table"),
+        HighScoreTableMarker,
+        Text::new(format_table(&high_scores, *mode, *difficulty, None)),
+        TextFont::from_font_size(18.0),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(15.0),
+            left: Val::Px(15.0),
+            ..default()
+        },
+        StateScoped(AppState::Menu),
+    ));
+}
+
+/// Follows the difficulty picked on the menu.
+fn update_high_score_table(
+    high_scores: Res<HighScores>,
+    mode: Res<GameMode>,
+    difficulty: Res<Difficulty>,
+    mut tables: Query<&mut Text, With<HighScoreTableMarker>>,
+) {
+    for mut table in tables.iter_mut() {
+        table.0 = format_table(&high_scores, *mode, *difficulty, None);
+    }
+}
+
+fn spawn_game_over_high_scores(
+    mut commands: Commands,
+    high_scores: Res<HighScores>,
+    latest_rank: Res<LatestRank>,
+    mode: Res<GameMode>,
+    difficulty: Res<Difficulty>,
+) {
+    commands.spawn((
+        Name::new("Game over high scores"),
+        Text::new(format_table(
+            &high_scores,
+            *mode,
+            *difficulty,
+            latest_rank.0,
+        )),
+        TextFont::from_font_size(18.0),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(60.0),
+            left: Val::Px(15.0),
+            ..default()
+        },
+        StateScoped(AppState::InGame),
+    ));
+}
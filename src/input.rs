@@ -0,0 +1,53 @@
+use crate::{
+    multiplayer::{PLAYER_KEYS, PlayerCount},
+    *,
+};
+
+pub struct InputPlugin;
+
+impl Plugin for InputPlugin {
+    fn build(&self, application: &mut App) {
+        application
+            .add_event::<ButtonPressed>()
+            .add_systems(Update, listen_for_input.in_set(LiveInputSet));
+    }
+}
+
+/// Systems turning keyboard, mouse and touch input into [`ButtonPressed`].
+///
+/// Other input sources (such as replays) gate this set to keep the player's
+/// hands off the bird.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LiveInputSet;
+
+/// A flap button press, from player one unless said otherwise.
+#[derive(Debug, Default, Clone, Copy, Event)]
+pub struct ButtonPressed {
+    pub player: usize,
+}
+
+fn listen_for_input(
+    mut event_pressed: EventWriter<ButtonPressed>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    touches: Res<Touches>,
+    gamepads: Query<(Entity, &Gamepad)>,
+    players: Res<PlayerCount>,
+) {
+    // Gamepads are handed out to players in the order they connected.
+    let mut gamepads: Vec<_> = gamepads.iter().collect();
+    gamepads.sort_by_key(|(entity, _)| *entity);
+
+    for (player, key) in PLAYER_KEYS.iter().enumerate().take(players.0) {
+        let pointer = player == 0
+            && (touches.iter_just_pressed().next().is_some()
+                || mouse.just_pressed(MouseButton::Left));
+        let gamepad = gamepads
+            .get(player)
+            .is_some_and(|(_, gamepad)| gamepad.just_pressed(GamepadButton::South));
+
+        if keyboard.just_pressed(*key) || pointer || gamepad {
+            event_pressed.send(ButtonPressed { player });
+        }
+    }
+}
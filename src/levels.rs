@@ -0,0 +1,733 @@
+//! Hand-authored levels: a fixed sequence of obstacles, loaded from
+//! `assets/levels/*.level.ron`, that ends once the last pipe is passed.
+//!
+//! A level is played by inserting an [`ActiveLevel`], which takes over
+//! obstacle spawning from the endless generator until the player quits back
+//! to the menu.
+
+use crate::{
+    autopilot::Autopilot,
+    bird::{BirdMarker, PlayerControlled},
+    config::GameConfig,
+    editor::LevelEditor,
+    game::{Playfield, Score},
+    motion::ObstacleKind,
+    multiplayer::{PlayerScores, single_player},
+    obstacles::{
+        GAP_OFFSET, Gap, ObstacleNotScored, ObstacleSpec, SpawnedObstacle, spawn_pipe_pair, spawn_x,
+    },
+    *,
+};
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    audio::Volume,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt, path::Path};
+
+/// Save format of [`LevelRecords`]; see [`storage`].
+pub const LEVEL_RECORDS_VERSION: u32 = 1;
+
+const STORAGE_KEY: &str = "levels";
+
+/// Where the asset server reads from on native builds.
+const ASSETS_DIR: &str = "assets";
+
+/// Points for collecting a coin.
+const COIN_POINTS: usize = 50;
+const PICKUP_RADIUS: f32 = 12.0;
+
+pub struct LevelsPlugin;
+
+impl Plugin for LevelsPlugin {
+    fn build(&self, application: &mut App) {
+        application
+            .init_asset::<Level>()
+            .register_asset_loader(LevelLoader)
+            .add_systems(OnEnter(AppState::Menu), spawn_level_hint)
+            .add_systems(Update, open_level_select.run_if(in_state(AppState::Menu)))
+            .add_systems(OnEnter(AppState::LevelSelect), spawn_level_select)
+            .add_systems(
+                Update,
+                handle_level_select.run_if(in_state(AppState::LevelSelect)),
+            )
+            .add_systems(
+                OnEnter(AppState::InGame),
+                restart_level.run_if(resource_exists::<ActiveLevel>),
+            )
+            .add_systems(
+                FixedUpdate,
+                (spawn_level_obstacles, collect_pickups, complete_level)
+                    .chain()
+                    .run_if(resource_exists::<ActiveLevel>)
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(in_state(GameState::Running)),
+            )
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                spawn_level_result.run_if(resource_exists::<ActiveLevel>),
+            )
+            .add_systems(
+                OnTransition {
+                    exited: AppState::InGame,
+                    entered: AppState::Menu,
+                },
+                leave_level,
+            );
+    }
+}
+
+/// Keeps the best result on every level between sessions, apart from
+/// [`LevelsPlugin`] so that simulated and bot runs can play levels without
+/// touching the player's records.
+pub struct LevelRecordsPlugin;
+
+impl Plugin for LevelRecordsPlugin {
+    fn build(&self, application: &mut App) {
+        application
+            .insert_resource(LevelRecords::load())
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                record_level_result
+                    .before(spawn_level_result)
+                    .run_if(resource_exists::<ActiveLevel>)
+                    // A shared run isn't any one player's.
+                    .run_if(single_player),
+            );
+    }
+}
+
+/// Every level in `assets/levels`, in no particular order.
+#[derive(AssetCollection, Resource, Default)]
+pub struct LevelAssets {
+    #[cfg_attr(not(target_arch = "wasm32"), asset(path = "levels", collection(typed)))]
+    // Web servers can't list a folder, so the web build names each level.
+    #[cfg_attr(
+        target_arch = "wasm32",
+        asset(
+            paths(
+                "levels/first-flight.level.ron",
+                "levels/moving-day.level.ron",
+                "levels/zigzag.level.ron"
+            ),
+            collection(typed)
+        )
+    )]
+    pub levels: Vec<Handle<Level>>,
+}
+
+/// A fixed run of obstacles.
+#[derive(Asset, TypePath, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Level {
+    /// Shown on the level select screen, and used to file best scores.
+    pub name: String,
+    /// Overrides the config's pipe speed for this level.
+    #[serde(default)]
+    pub pipe_speed: Option<f32>,
+    pub obstacles: Vec<LevelObstacle>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LevelObstacle {
+    /// Horizontal distance from the previous obstacle, or from the right
+    /// edge of the screen for the first.
+    pub spacing: f32,
+    /// World-space height of the middle of the gap.
+    pub gap_y: f32,
+    pub gap_size: f32,
+    #[serde(default)]
+    pub kind: ObstacleKind,
+    #[serde(default)]
+    pub pickup: Option<PickupKind>,
+}
+
+/// Something to collect in the middle of a gap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PickupKind {
+    Coin,
+}
+
+impl PickupKind {
+    pub fn points(self) -> usize {
+        match self {
+            PickupKind::Coin => COIN_POINTS,
+        }
+    }
+}
+
+impl Level {
+    pub fn from_ron(contents: &str) -> Result<Self, LevelError> {
+        let level: Level = ron::from_str(contents).map_err(LevelError::Parse)?;
+        level.validate()?;
+
+        Ok(level)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, LevelError> {
+        let contents = std::fs::read_to_string(path)?;
+
+        Self::from_ron(&contents)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), LevelError> {
+        self.validate()?;
+
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(LevelError::Serialize)?;
+        std::fs::write(path, contents)?;
+
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), LevelError> {
+        if self.obstacles.is_empty() {
+            return Err(LevelError::Invalid(
+                "a level needs at least one obstacle".into(),
+            ));
+        }
+
+        if let Some(speed) = self.pipe_speed
+            && (!speed.is_finite() || speed <= 0.0)
+        {
+            return Err(LevelError::Invalid(format!(
+                "pipe_speed must be greater than zero, got {speed}"
+            )));
+        }
+
+        for (index, obstacle) in self.obstacles.iter().enumerate() {
+            if !obstacle.spacing.is_finite() || obstacle.spacing < 0.0 {
+                return Err(LevelError::Invalid(format!(
+                    "obstacle {index}: spacing can't be negative, got {}",
+                    obstacle.spacing
+                )));
+            }
+
+            if !obstacle.gap_size.is_finite() || obstacle.gap_size <= 0.0 {
+                return Err(LevelError::Invalid(format!(
+                    "obstacle {index}: gap_size must be greater than zero, got {}",
+                    obstacle.gap_size
+                )));
+            }
+
+            if !obstacle.gap_y.is_finite() {
+                return Err(LevelError::Invalid(format!(
+                    "obstacle {index}: gap_y must be a number"
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn pipe_speed(&self, config: &GameConfig) -> f32 {
+        self.pipe_speed.unwrap_or(config.pipe_speed)
+    }
+}
+
+#[derive(Debug)]
+pub enum LevelError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+    Serialize(ron::Error),
+    Invalid(String),
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelError::Io(error) => write!(f, "could not read level: {error}"),
+            LevelError::Parse(error) => write!(f, "invalid level: {error}"),
+            LevelError::Serialize(error) => write!(f, "could not write level: {error}"),
+            LevelError::Invalid(reason) => write!(f, "invalid level: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for LevelError {}
+
+impl From<std::io::Error> for LevelError {
+    fn from(error: std::io::Error) -> Self {
+        LevelError::Io(error)
+    }
+}
+
+#[derive(Default)]
+struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    type Asset = Level;
+    type Settings = ();
+    type Error = LevelError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        let contents = String::from_utf8_lossy(&bytes);
+        Level::from_ron(&contents)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
+
+/// The level being played, and how far through it the run is.
+#[derive(Debug, Clone, Resource)]
+pub struct ActiveLevel {
+    pub level: Level,
+    /// Index of the next obstacle to spawn.
+    pub next: usize,
+    /// How far the pipes have scrolled since the run started.
+    pub scrolled: f32,
+    /// Value of `scrolled` at which the next obstacle is due.
+    pub next_at: f32,
+    /// Set once every obstacle has been passed.
+    pub completed: bool,
+}
+
+impl ActiveLevel {
+    pub fn new(level: Level) -> Self {
+        let mut active = Self {
+            level,
+            next: 0,
+            scrolled: 0.0,
+            next_at: 0.0,
+            completed: false,
+        };
+        active.restart();
+
+        active
+    }
+
+    /// Rewinds to the first obstacle.
+    pub fn restart(&mut self) {
+        self.next = 0;
+        self.scrolled = 0.0;
+        self.next_at = self
+            .level
+            .obstacles
+            .first()
+            .map_or(0.0, |obstacle| obstacle.spacing);
+        self.completed = false;
+    }
+}
+
+/// Whether the run that just ended finished its level.
+pub fn level_completed(level: Option<Res<ActiveLevel>>) -> bool {
+    level.is_some_and(|level| level.completed)
+}
+
+/// Best results for every level played, persisted between sessions.
+#[derive(Debug, Clone, Resource, Serialize, Deserialize)]
+pub struct LevelRecords {
+    pub version: u32,
+    pub levels: BTreeMap<String, LevelRecord>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LevelRecord {
+    pub best: usize,
+    pub completed: bool,
+}
+
+impl Default for LevelRecords {
+    fn default() -> Self {
+        Self {
+            version: LEVEL_RECORDS_VERSION,
+            levels: BTreeMap::new(),
+        }
+    }
+}
+
+impl LevelRecords {
+    /// Reads the saved records, starting afresh if there aren't readable
+    /// ones.
+    pub fn load() -> Self {
+        storage::load_ron(STORAGE_KEY, LEVEL_RECORDS_VERSION).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        storage::save_ron(STORAGE_KEY, self);
+    }
+
+    pub fn get(&self, level: &str) -> LevelRecord {
+        self.levels.get(level).copied().unwrap_or_default()
+    }
+
+    /// Records a run, returning whether it beat the previous best.
+    pub fn submit(&mut self, level: &str, score: usize, completed: bool) -> bool {
+        let record = self.levels.entry(level.to_string()).or_default();
+        let new_best = score > record.best;
+
+        record.best = record.best.max(score);
+        record.completed |= completed;
+
+        new_best
+    }
+}
+
+/// Where the most recent level run stands against the records.
+#[derive(Debug, Default, Clone, Copy, Resource)]
+struct LatestLevelResult {
+    new_best: bool,
+}
+
+/// A collectible sitting in the gap of `obstacle`.
+#[derive(Debug, Clone, Copy, Component)]
+pub struct Pickup {
+    pub kind: PickupKind,
+    pub obstacle: Entity,
+}
+
+fn restart_level(mut level: ResMut<ActiveLevel>) {
+    level.restart();
+}
+
+fn spawn_level_obstacles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut level: ResMut<ActiveLevel>,
+    playfield: Res<Playfield>,
+    assets: Res<SpriteAssets>,
+    config: Res<GameConfig>,
+) {
+    let speed = level.level.pipe_speed(&config);
+    level.scrolled += speed * time.delta_secs();
+
+    while let Some(&obstacle) = level.level.obstacles.get(level.next) {
+        if level.scrolled < level.next_at {
+            break;
+        }
+
+        // Place it where it would be had it spawned exactly on time.
+        let x = spawn_x(&playfield, &config) - (level.scrolled - level.next_at);
+        let spawned = spawn_pipe_pair(
+            &mut commands,
+            &assets,
+            &config,
+            ObstacleSpec {
+                x,
+                gap_center: obstacle.gap_y,
+                gap_size: obstacle.gap_size,
+                speed,
+                kind: obstacle.kind,
+            },
+        );
+
+        if let Some(kind) = obstacle.pickup {
+            spawn_pickup(&mut commands, spawned, kind);
+        }
+
+        level.next += 1;
+        if let Some(following) = level.level.obstacles.get(level.next) {
+            level.next_at += following.spacing;
+        }
+    }
+}
+
+fn spawn_pickup(commands: &mut Commands, obstacle: SpawnedObstacle, kind: PickupKind) {
+    let sprite = match kind {
+        PickupKind::Coin => {
+            Sprite::from_color(Color::srgb(1.0, 0.8, 0.1), Vec2::splat(PICKUP_RADIUS * 2.0))
+        }
+    };
+
+    commands.entity(obstacle.sprites).with_children(|parent| {
+        parent.spawn((
+            Name::new("Pickup"),
+            Pickup {
+                kind,
+                obstacle: obstacle.body,
+            },
+            sprite,
+            Transform::from_xyz(0.0, GAP_OFFSET, 1.0),
+        ));
+    });
+}
+
+fn collect_pickups(
+    mut commands: Commands,
+    pickups: Query<(Entity, &Pickup)>,
+    obstacles: Query<(&Transform, &Gap)>,
+    birds: Query<(&Transform, Option<&PlayerControlled>), With<BirdMarker>>,
+    mut score: ResMut<Score>,
+    mut player_scores: ResMut<PlayerScores>,
+    audio: Res<AudioAssets>,
+) {
+    for (entity, pickup) in pickups.iter() {
+        let Ok((transform, gap)) = obstacles.get(pickup.obstacle) else {
+            continue;
+        };
+        let position = transform.translation.truncate() + Vec2::new(0.0, gap.offset);
+
+        let reach = bird::BIRD_RADIUS * bird::BIRD_SCALE + PICKUP_RADIUS;
+        let collector = birds
+            .iter()
+            .find(|(bird, _)| bird.translation.truncate().distance(position) <= reach);
+
+        if let Some((_, controlled)) = collector {
+            score.add_bonus(pickup.kind.points());
+            if let Some(controlled) = controlled {
+                player_scores.add(controlled.player, pickup.kind.points());
+            }
+            commands.entity(entity).despawn_recursive();
+            commands.spawn((
+                Name::new("Pickup audio"),
+                PlaybackSettings::DESPAWN.with_volume(Volume::new(0.1)),
+                AudioPlayer::new(audio.point.clone()),
+            ));
+        }
+    }
+}
+
+fn complete_level(
+    mut level: ResMut<ActiveLevel>,
+    remaining: Query<(), ObstacleNotScored>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let all_spawned = level.next >= level.level.obstacles.len();
+
+    if all_spawned && remaining.is_empty() && !level.completed {
+        level.completed = true;
+        next_state.set(GameState::GameOver);
+    }
+}
+
+fn record_level_result(
+    mut commands: Commands,
+    level: Res<ActiveLevel>,
+    score: Res<Score>,
+    mut records: ResMut<LevelRecords>,
+    editor: Option<Res<LevelEditor>>,
+    autopilot: Option<Res<Autopilot>>,
+) {
+    // Play-tests from the editor and bot runs don't count.
+    let flown_by_bot = autopilot.is_some_and(|autopilot| autopilot.enabled);
+    if editor.is_some() || flown_by_bot {
+        commands.insert_resource(LatestLevelResult::default());
+        return;
+    }
+
+    let new_best = records.submit(&level.level.name, score.value(), level.completed);
+
+    if new_best || level.completed {
+        records.save();
+    }
+
+    commands.insert_resource(LatestLevelResult { new_best });
+}
+
+fn spawn_level_result(
+    mut commands: Commands,
+    level: Res<ActiveLevel>,
+    records: Option<Res<LevelRecords>>,
+    latest: Option<Res<LatestLevelResult>>,
+) {
+    let headline = if level.completed {
+        "Level complete!"
+    } else {
+        "Try again"
+    };
+    let mut text = format!("{}\n{headline}", level.level.name);
+
+    if let Some(records) = records {
+        text.push_str(&format!("\nBest {}", records.get(&level.level.name).best));
+    }
+
+    if latest.is_some_and(|latest| latest.new_best) {
+        text.push_str("\nNew best!");
+    }
+
+    commands.spawn((
+        Name::new("Level result"),
+        Text::new(text),
+        TextFont::from_font_size(32.0),
+        TextLayout::new_with_justify(JustifyText::Center),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(120.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        StateScoped(AppState::InGame),
+    ));
+}
+
+fn leave_level(mut commands: Commands) {
+    commands.remove_resource::<ActiveLevel>();
+}
+
+fn spawn_level_hint(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Level select hint"),
+        Text::new("L: Levels"),
+        TextFont::from_font_size(18.0),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(15.0),
+            right: Val::Px(15.0),
+            ..default()
+        },
+        StateScoped(AppState::Menu),
+    ));
+}
+
+fn open_level_select(keys: Res<ButtonInput<KeyCode>>, mut next_state: ResMut<NextState<AppState>>) {
+    if keys.just_pressed(KeyCode::KeyL) {
+        next_state.set(AppState::LevelSelect);
+    }
+}
+
+#[derive(Debug, Clone, Component)]
+pub enum LevelSelectButton {
+    Play(Handle<Level>),
+    Edit(Handle<Level>),
+    Back,
+}
+
+fn spawn_level_select(
+    mut commands: Commands,
+    level_assets: Option<Res<LevelAssets>>,
+    levels: Res<Assets<Level>>,
+    records: Option<Res<LevelRecords>>,
+) {
+    let mut handles: Vec<&Handle<Level>> = level_assets
+        .iter()
+        .flat_map(|level_assets| level_assets.levels.iter())
+        .collect();
+    // Folders load in whatever order the file system lists them.
+    handles.sort_by_key(|handle| handle.path().map(ToString::to_string));
+
+    let entries: Vec<(Handle<Level>, String)> = handles
+        .into_iter()
+        .filter_map(|handle| {
+            let level = levels.get(handle)?;
+            let mut label = level.name.clone();
+            if let Some(record) = records.as_ref().map(|records| records.get(&level.name)) {
+                label.push_str(&format!("  -  best {}", record.best));
+                if record.completed {
+                    label.push_str("  *");
+                }
+            }
+
+            Some((handle.clone(), label))
+        })
+        .collect();
+
+    commands
+        .spawn((
+            Name::new("Level select"),
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(15.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.05, 0.05, 0.1)),
+            StateScoped(AppState::LevelSelect),
+        ))
+        .with_children(|parent| {
+            parent.spawn((Text::new("Levels"), TextFont::from_font_size(48.0)));
+
+            if entries.is_empty() {
+                parent.spawn(Text::new("No levels found"));
+            }
+
+            for (handle, label) in entries {
+                parent
+                    .spawn(Node {
+                        column_gap: Val::Px(10.0),
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        spawn_level_button(
+                            row,
+                            LevelSelectButton::Edit(handle.clone()),
+                            "Edit",
+                            70.0,
+                        );
+                        spawn_level_button(row, LevelSelectButton::Play(handle), &label, 340.0);
+                    });
+            }
+
+            spawn_level_button(parent, LevelSelectButton::Back, "Back", 420.0);
+        });
+}
+
+fn spawn_level_button(
+    parent: &mut ChildBuilder,
+    button: LevelSelectButton,
+    label: &str,
+    width: f32,
+) {
+    parent
+        .spawn((
+            Name::new(label.to_string()),
+            button,
+            Button,
+            Node {
+                width: Val::Px(width),
+                padding: UiRect::all(Val::Px(10.0)),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
+        ))
+        .with_children(|button_parent| {
+            button_parent.spawn(Text::new(label));
+        });
+}
+
+fn handle_level_select(
+    mut commands: Commands,
+    buttons: Query<(&Interaction, &LevelSelectButton), Changed<Interaction>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    levels: Res<Assets<Level>>,
+    asset_server: Res<AssetServer>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if keys.just_pressed(KeyCode::Escape) {
+        next_state.set(AppState::Menu);
+        return;
+    }
+
+    for (interaction, button) in buttons.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match button {
+            LevelSelectButton::Play(handle) => {
+                let Some(level) = levels.get(handle) else {
+                    continue;
+                };
+
+                commands.insert_resource(ActiveLevel::new(level.clone()));
+                next_state.set(AppState::InGame);
+            }
+            LevelSelectButton::Edit(handle) => {
+                let (Some(level), Some(path)) =
+                    (levels.get(handle), asset_server.get_path(handle.id()))
+                else {
+                    continue;
+                };
+
+                // Levels are saved back over the file they were loaded from.
+                let path = Path::new(ASSETS_DIR).join(path.path());
+                commands.insert_resource(LevelEditor::new(level.clone(), path));
+                next_state.set(AppState::Editor);
+            }
+            LevelSelectButton::Back => next_state.set(AppState::Menu),
+        }
+    }
+}
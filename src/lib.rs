@@ -0,0 +1,136 @@
+use bevy::{app::PluginGroupBuilder, asset::AssetMetaCheck, prelude::*};
+use bevy_asset_loader::prelude::*;
+use bevy_rand::prelude::*;
+use consts::*;
+
+pub mod autopilot;
+pub mod bird;
+pub mod cli;
+pub mod collision;
+pub mod config;
+pub mod daily;
+pub mod date;
+pub mod death;
+pub mod difficulty;
+pub mod editor;
+pub mod evolution;
+pub mod game;
+pub mod generator;
+pub mod ghost;
+pub mod gym;
+pub mod headless;
+pub mod highscores;
+pub mod input;
+pub mod levels;
+pub mod motion;
+pub mod multiplayer;
+pub mod obstacles;
+pub mod pause;
+pub mod physics;
+pub mod replay;
+pub mod results;
+pub mod storage;
+pub mod verify;
+pub mod windowed;
+
+/// Everything needed to play birb inside an existing Bevy app.
+///
+/// This doesn't bring its own window or renderer: add it alongside
+/// `DefaultPlugins` (see [`windowed::WindowedPlugin`]) or the headless shell
+/// in [`headless::HeadlessPlugin`].
+pub struct BirbPlugins {
+    pub seed: u64,
+}
+
+impl Default for BirbPlugins {
+    fn default() -> Self {
+        Self { seed: 42 }
+    }
+}
+
+impl PluginGroup for BirbPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add(EntropyPlugin::<WyRand>::with_seed(self.seed.to_be_bytes()))
+            .add(game::GamePlugin { seed: self.seed })
+            .add(config::GameConfigPlugin)
+            .add(physics::PhysicsPlugin)
+            .add(collision::CollisionPlugin)
+            .add(bird::BirdPlugin)
+            .add(death::DeathPlugin)
+            .add(input::InputPlugin)
+            .add(multiplayer::MultiplayerPlugin)
+            .add(obstacles::ObstaclePlugin)
+            .add(difficulty::DifficultyPlugin)
+            .add(levels::LevelsPlugin)
+            .add(levels::LevelRecordsPlugin)
+            .add(editor::EditorPlugin)
+            .add(motion::MotionPlugin)
+            .add(highscores::HighScoresPlugin)
+            .add(daily::DailyPlugin)
+            .add(daily::DailyBestsPlugin)
+            .add(ghost::GhostPlugin)
+            .add(results::ResultsPlugin)
+            .add(pause::PausePlugin)
+            .add(autopilot::AutopilotPlugin)
+    }
+}
+
+// Both collections derive `Default` so the headless build can run the game
+// against empty handles without ever touching the asset server.
+#[derive(AssetCollection, Resource, Default)]
+pub struct SpriteAssets {
+    #[asset(path = "sprites/background-day.png")]
+    pub background_day: Handle<Image>,
+    #[asset(path = "sprites/birb.png")]
+    pub birb: Handle<Image>,
+    #[asset(path = "sprites/pipe-green.png")]
+    pub green_pipe: Handle<Image>,
+    #[asset(path = "sprites/base.png")]
+    pub ground: Handle<Image>,
+    #[asset(path = "sprites/message.png")]
+    pub start_screen_instructions: Handle<Image>,
+    #[asset(path = "sprites/background-night.png")]
+    pub start_screen_background: Handle<Image>,
+    #[asset(path = "sprites/gameover.png")]
+    pub game_over: Handle<Image>,
+}
+
+#[derive(AssetCollection, Resource, Default)]
+pub struct AudioAssets {
+    #[asset(path = "audio/die.ogg")]
+    pub die: Handle<AudioSource>,
+    #[asset(path = "audio/hit.ogg")]
+    pub hit: Handle<AudioSource>,
+    #[asset(path = "audio/point.ogg")]
+    pub point: Handle<AudioSource>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, States)]
+pub enum AppState {
+    #[default]
+    Loading,
+    Menu,
+    LevelSelect,
+    Editor,
+    InGame,
+    /// Passed through on the way back into `InGame`, so a restart runs the
+    /// usual exit and enter systems for a fresh run.
+    Restarting,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, States)]
+pub enum GameState {
+    #[default]
+    Running,
+    Paused,
+    /// The last bird has been hit and is falling; see [`death`].
+    Dying,
+    GameOver,
+}
+
+pub mod consts {
+    pub const WINDOW_WIDTH: f32 = 640.0;
+    pub const WINDOW_HEIGHT: f32 = 1136.0;
+    pub const TICK_RATE_HZ: f64 = 64.0;
+}
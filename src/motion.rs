@@ -0,0 +1,155 @@
+//! Obstacles that move on their own, on top of scrolling left.
+//!
+//! Each kind is a component with a system of its own. Which obstacles get
+//! one is up to the [`crate::generator`], which mixes them in as the
+//! difficulty curve's `moving_share` rises.
+
+use crate::{config::GameConfig, obstacles::Gap, *};
+use bevy::ecs::system::EntityCommands;
+use bevy_rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+
+pub struct MotionPlugin;
+
+impl Plugin for MotionPlugin {
+    fn build(&self, application: &mut App) {
+        application.add_systems(
+            FixedUpdate,
+            (
+                oscillate,
+                breathe.before(obstacles::fit_pipes_to_gap),
+                shift,
+            )
+                .run_if(in_state(AppState::InGame))
+                .run_if(in_state(GameState::Running)),
+        );
+    }
+}
+
+const OSCILLATION_AMPLITUDE: f32 = 60.0;
+const OSCILLATION_PERIOD: f32 = 2.0;
+
+/// How much a breathing gap opens beyond its planned size.
+const BREATHING_AMPLITUDE: f32 = 50.0;
+const BREATHING_PERIOD: f32 = 1.5;
+
+pub const SHIFT_DISTANCE: f32 = 70.0;
+/// Seconds a shifting obstacle takes to move.
+const SHIFT_DURATION: f32 = 0.35;
+/// How far ahead of the bird an obstacle is when it starts shifting.
+const SHIFT_TRIGGER_DISTANCE: f32 = 220.0;
+
+/// How an obstacle moves, as picked by the generator or a level.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ObstacleKind {
+    #[default]
+    Static,
+    /// Bobs up and down around where it was spawned.
+    Oscillating,
+    /// The gap keeps opening wider and closing back to its planned size.
+    Breathing,
+    /// Jumps this far up (or down, when negative) once the bird gets close.
+    Shifting(f32),
+}
+
+impl ObstacleKind {
+    /// Furthest the middle of the gap strays from where it was planned.
+    pub fn max_displacement(&self) -> f32 {
+        match self {
+            ObstacleKind::Static | ObstacleKind::Breathing => 0.0,
+            ObstacleKind::Oscillating => OSCILLATION_AMPLITUDE,
+            ObstacleKind::Shifting(distance) => distance.abs(),
+        }
+    }
+
+    /// Adds this kind's motion component to a freshly spawned obstacle.
+    pub fn insert(self, obstacle: &mut EntityCommands, gap: &Gap) {
+        match self {
+            ObstacleKind::Static => {}
+            ObstacleKind::Oscillating => {
+                obstacle.insert(Oscillating::default());
+            }
+            ObstacleKind::Breathing => {
+                obstacle.insert(Breathing {
+                    closed_size: gap.size,
+                    elapsed: 0.0,
+                });
+            }
+            ObstacleKind::Shifting(distance) => {
+                obstacle.insert(Shifting {
+                    distance,
+                    elapsed: None,
+                });
+            }
+        }
+    }
+}
+
+/// Moves an obstacle up and down along a sine wave.
+#[derive(Debug, Default, Clone, Copy, Component)]
+pub struct Oscillating {
+    pub elapsed: f32,
+}
+
+/// Opens and closes an obstacle's gap.
+#[derive(Debug, Clone, Copy, Component)]
+pub struct Breathing {
+    /// The gap size at its narrowest.
+    pub closed_size: f32,
+    pub elapsed: f32,
+}
+
+/// Moves an obstacle once, as the bird approaches.
+#[derive(Debug, Clone, Copy, Component)]
+pub struct Shifting {
+    pub distance: f32,
+    /// Seconds since the shift started, once it has.
+    pub elapsed: Option<f32>,
+}
+
+fn oscillate(time: Res<Time>, mut obstacles: Query<(&mut Oscillating, &mut Velocity)>) {
+    let frequency = TAU / OSCILLATION_PERIOD;
+
+    for (mut oscillating, mut velocity) in obstacles.iter_mut() {
+        // The derivative of `amplitude * sin(frequency * t)`, so the
+        // obstacle starts at the height it was planned at.
+        velocity.linvel.y =
+            OSCILLATION_AMPLITUDE * frequency * (frequency * oscillating.elapsed).cos();
+        oscillating.elapsed += time.delta_secs();
+    }
+}
+
+fn breathe(time: Res<Time>, mut obstacles: Query<(&mut Breathing, &mut Gap)>) {
+    let frequency = TAU / BREATHING_PERIOD;
+
+    for (mut breathing, mut gap) in obstacles.iter_mut() {
+        breathing.elapsed += time.delta_secs();
+
+        let openness = 0.5 - 0.5 * (frequency * breathing.elapsed).cos();
+        gap.size = breathing.closed_size + BREATHING_AMPLITUDE * openness;
+    }
+}
+
+fn shift(
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    mut obstacles: Query<(&mut Shifting, &Transform, &mut Velocity)>,
+) {
+    for (mut shifting, transform, mut velocity) in obstacles.iter_mut() {
+        let Some(elapsed) = shifting.elapsed else {
+            if transform.translation.x - config.birb_x < SHIFT_TRIGGER_DISTANCE {
+                shifting.elapsed = Some(0.0);
+                velocity.linvel.y = shifting.distance / SHIFT_DURATION;
+            }
+            continue;
+        };
+
+        let elapsed = elapsed + time.delta_secs();
+        shifting.elapsed = Some(elapsed);
+
+        if elapsed >= SHIFT_DURATION {
+            velocity.linvel.y = 0.0;
+        }
+    }
+}
@@ -0,0 +1,250 @@
+//! Local multiplayer: up to [`MAX_PLAYERS`] birds flying through the same
+//! pipes, each on its own button.
+//!
+//! A bird that hits something drops out, and the round carries on until the
+//! last one goes down. Everyone still flying scores for each pipe passed, so
+//! outlasting the others is how to win.
+
+use crate::{bird::PlayerControlled, game::OBSTACLE_POINTS, obstacles::PlayerPassedObstacle, *};
+
+pub struct MultiplayerPlugin;
+
+impl Plugin for MultiplayerPlugin {
+    fn build(&self, application: &mut App) {
+        application
+            .init_resource::<PlayerCount>()
+            .init_resource::<PlayerScores>()
+            .add_systems(OnEnter(AppState::Menu), spawn_player_selector)
+            .add_systems(
+                Update,
+                (select_player_count, update_player_selector)
+                    .chain()
+                    .run_if(in_state(AppState::Menu)),
+            )
+            .add_systems(OnEnter(AppState::InGame), reset_player_scores)
+            .add_systems(
+                OnEnter(AppState::InGame),
+                spawn_player_scores.run_if(not(single_player)),
+            )
+            .add_systems(
+                FixedUpdate,
+                score_surviving_players
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(in_state(GameState::Running)),
+            )
+            .add_systems(
+                Update,
+                update_player_scores
+                    .run_if(not(single_player))
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                spawn_round_result.run_if(not(single_player)),
+            );
+    }
+}
+
+pub const MAX_PLAYERS: usize = 4;
+
+/// The key each player flaps with. Player one can also click or tap, and
+/// each player's gamepad is the one at the same position in connection
+/// order.
+pub const PLAYER_KEYS: [KeyCode; MAX_PLAYERS] =
+    [KeyCode::Space, KeyCode::Enter, KeyCode::KeyQ, KeyCode::KeyM];
+
+/// Sprite tints telling the birds apart, in player order.
+pub const PLAYER_TINTS: [Color; MAX_PLAYERS] = [
+    Color::WHITE,
+    Color::srgb(1.0, 0.55, 0.55),
+    Color::srgb(0.55, 0.75, 1.0),
+    Color::srgb(0.6, 1.0, 0.6),
+];
+
+/// Readable text colours matching [`PLAYER_TINTS`].
+const PLAYER_TEXT_COLORS: [Color; MAX_PLAYERS] = [
+    Color::srgb(1.0, 0.9, 0.3),
+    Color::srgb(1.0, 0.45, 0.45),
+    Color::srgb(0.45, 0.65, 1.0),
+    Color::srgb(0.45, 0.95, 0.45),
+];
+
+const KEY_NAMES: [&str; MAX_PLAYERS] = ["Space", "Enter", "Q", "M"];
+
+/// How many birds take part in a run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Resource)]
+pub struct PlayerCount(pub usize);
+
+impl Default for PlayerCount {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+pub fn single_player(players: Res<PlayerCount>) -> bool {
+    players.0 <= 1
+}
+
+/// Each player's score for the current run, in player order.
+#[derive(Debug, Default, Clone, Resource)]
+pub struct PlayerScores(pub Vec<usize>);
+
+impl PlayerScores {
+    pub fn add(&mut self, player: usize, points: usize) {
+        if let Some(score) = self.0.get_mut(player) {
+            *score += points;
+        }
+    }
+
+    /// The single highest scorer, if nobody is tied with them.
+    pub fn winner(&self) -> Option<usize> {
+        let best = *self.0.iter().max()?;
+        let mut leaders = (0..self.0.len()).filter(|player| self.0[*player] == best);
+
+        match (leaders.next(), leaders.next()) {
+            (Some(player), None) => Some(player),
+            _ => None,
+        }
+    }
+}
+
+fn reset_player_scores(mut scores: ResMut<PlayerScores>, players: Res<PlayerCount>) {
+    scores.0 = vec![0; players.0];
+}
+
+fn score_surviving_players(
+    mut passed_obstacle: EventReader<PlayerPassedObstacle>,
+    birds: Query<&PlayerControlled>,
+    mut scores: ResMut<PlayerScores>,
+) {
+    for _ in passed_obstacle.read() {
+        for bird in birds.iter() {
+            scores.add(bird.player, OBSTACLE_POINTS);
+        }
+    }
+}
+
+#[derive(Debug, Component)]
+pub struct PlayerSelectorMarker;
+
+fn spawn_player_selector(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Player selector"),
+        PlayerSelectorMarker,
+        Text::default(),
+        TextFont::from_font_size(18.0),
+        // Bottom left, clear of the high score table in the top left.
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(15.0),
+            left: Val::Px(15.0),
+            ..default()
+        },
+        StateScoped(AppState::Menu),
+    ));
+}
+
+fn select_player_count(keys: Res<ButtonInput<KeyCode>>, mut players: ResMut<PlayerCount>) {
+    let digits = [
+        KeyCode::Digit1,
+        KeyCode::Digit2,
+        KeyCode::Digit3,
+        KeyCode::Digit4,
+    ];
+
+    if let Some(count) = digits.iter().position(|key| keys.just_pressed(*key)) {
+        players.0 = count + 1;
+    }
+}
+
+fn update_player_selector(
+    players: Res<PlayerCount>,
+    mut selector: Query<&mut Text, With<PlayerSelectorMarker>>,
+) {
+    let Ok(mut selector) = selector.get_single_mut() else {
+        return;
+    };
+
+    let mut text = format!("Players: {} (1-4)", players.0);
+    if players.0 > 1 {
+        for (player, key) in KEY_NAMES.iter().take(players.0).enumerate() {
+            text.push_str(&format!("\nP{}: {key}", player + 1));
+        }
+    }
+
+    selector.0 = text;
+}
+
+#[derive(Debug, Component)]
+pub struct PlayerScoreMarker {
+    pub player: usize,
+}
+
+fn spawn_player_scores(mut commands: Commands, players: Res<PlayerCount>) {
+    commands
+        .spawn((
+            Name::new("Player scores"),
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(15.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                column_gap: Val::Px(30.0),
+                ..default()
+            },
+            StateScoped(AppState::InGame),
+        ))
+        .with_children(|parent| {
+            for (player, color) in PLAYER_TEXT_COLORS.into_iter().enumerate().take(players.0) {
+                parent.spawn((
+                    PlayerScoreMarker { player },
+                    Text::default(),
+                    TextFont::from_font_size(24.0),
+                    TextColor(color),
+                ));
+            }
+        });
+}
+
+fn update_player_scores(
+    scores: Res<PlayerScores>,
+    birds: Query<&PlayerControlled>,
+    mut displays: Query<(&PlayerScoreMarker, &mut Text)>,
+) {
+    for (marker, mut text) in displays.iter_mut() {
+        let score = scores.0.get(marker.player).copied().unwrap_or_default();
+        let flying = birds.iter().any(|bird| bird.player == marker.player);
+
+        text.0 = if flying {
+            format!("P{} {score}", marker.player + 1)
+        } else {
+            format!("P{} {score} out", marker.player + 1)
+        };
+    }
+}
+
+fn spawn_round_result(mut commands: Commands, scores: Res<PlayerScores>) {
+    let (headline, color) = match scores.winner() {
+        Some(player) => (
+            format!("Player {} wins!", player + 1),
+            PLAYER_TEXT_COLORS[player],
+        ),
+        None => ("Draw!".to_string(), Color::WHITE),
+    };
+
+    commands.spawn((
+        Name::new("Round result"),
+        Text::new(headline),
+        TextFont::from_font_size(40.0),
+        TextColor(color),
+        TextLayout::new_with_justify(JustifyText::Center),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(80.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        StateScoped(AppState::InGame),
+    ));
+}
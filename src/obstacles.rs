@@ -0,0 +1,351 @@
+use crate::{
+    collision::Hazard,
+    config::GameConfig,
+    difficulty::CurrentPacing,
+    game::{PendingSeed, Playfield, RunSeed, Score},
+    generator::PipeGenerator,
+    levels::ActiveLevel,
+    motion::ObstacleKind,
+    physics::{InterpolatedVisual, PhysicsHistory},
+    *,
+};
+use bevy_rapier2d::prelude::*;
+use rand_core::SeedableRng;
+
+pub struct ObstaclePlugin;
+
+impl Plugin for ObstaclePlugin {
+    fn build(&self, application: &mut App) {
+        application
+            .add_event::<PlayerPassedObstacle>()
+            .init_resource::<PipeGenerator>()
+            .insert_resource(ObstacleSpawnTimer {
+                timer: Timer::from_seconds(
+                    GameConfig::default().time_between_spawn,
+                    TimerMode::Repeating,
+                ),
+            })
+            .add_systems(
+                OnEnter(AppState::InGame),
+                (
+                    reset_game_state,
+                    seed_rng,
+                    spawn_obstacle.run_if(not(resource_exists::<ActiveLevel>)),
+                    reset_timer,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    track_obstacle_movement,
+                    fit_pipes_to_gap,
+                    score_obstacle,
+                    (
+                        tick_spawn_timer,
+                        spawn_obstacle
+                            .run_if(spawn_timer_finished)
+                            .run_if(not(resource_exists::<ActiveLevel>)),
+                    )
+                        .chain(),
+                )
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(in_state(GameState::Running)),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub struct ObstacleSpawnTimer {
+    pub timer: Timer,
+}
+
+fn reset_timer(mut timer: ResMut<ObstacleSpawnTimer>, pacing: CurrentPacing) {
+    timer.timer = Timer::from_seconds(pacing.get().spawn_interval, TimerMode::Repeating);
+}
+
+pub fn seed_rng(
+    mut rng: GlobalEntropy<WyRand>,
+    mut seed: ResMut<RunSeed>,
+    mut pending_seed: ResMut<PendingSeed>,
+) {
+    if let Some(pending_seed) = pending_seed.0.take() {
+        seed.0 = pending_seed;
+    }
+
+    **rng = Entropy::from_seed(seed.0.to_be_bytes());
+}
+
+fn reset_game_state(
+    mut next_state: ResMut<NextState<GameState>>,
+    mut score: ResMut<Score>,
+    mut generator: ResMut<PipeGenerator>,
+) {
+    next_state.set(GameState::Running);
+    score.reset();
+    generator.reset();
+}
+
+/// Half the height of a single pipe's collider.
+const PIPE_HALF_HEIGHT: f32 = 400.0;
+/// Centre of the gap relative to the obstacle's origin.
+pub const GAP_OFFSET: f32 = -200.0;
+
+#[derive(Default, Component)]
+pub struct ObstacleMarker;
+
+/// The opening between a pipe pair, relative to the obstacle's origin.
+#[derive(Debug, Clone, Copy, Component)]
+pub struct Gap {
+    pub offset: f32,
+    pub size: f32,
+}
+
+impl Default for Gap {
+    fn default() -> Self {
+        Self::with_size(200.0)
+    }
+}
+
+impl Gap {
+    pub fn with_size(size: f32) -> Self {
+        Self {
+            offset: GAP_OFFSET,
+            size,
+        }
+    }
+
+    /// Offset of the top pipe's centre from the obstacle's origin.
+    pub fn top_pipe_offset(&self) -> f32 {
+        self.offset + self.size / 2.0 + PIPE_HALF_HEIGHT
+    }
+
+    /// Offset of the bottom pipe's centre from the obstacle's origin.
+    pub fn bottom_pipe_offset(&self) -> f32 {
+        self.offset - self.size / 2.0 - PIPE_HALF_HEIGHT
+    }
+
+    /// World-space bottom and top of the gap for an obstacle at `obstacle_y`.
+    pub fn bounds(&self, obstacle_y: f32) -> (f32, f32) {
+        let center = obstacle_y + self.offset;
+
+        (center - self.size / 2.0, center + self.size / 2.0)
+    }
+}
+
+/// One of an obstacle's pipes, collider or sprite, kept lined up with the
+/// edge of its [`Gap`].
+#[derive(Debug, Clone, Copy, Component)]
+pub struct PipeSection {
+    pub obstacle: Entity,
+    pub side: PipeSide,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PipeSide {
+    Top,
+    Bottom,
+}
+
+#[derive(Default, Component)]
+pub struct AlreadyScoredMarker;
+
+#[derive(Default, Event)]
+pub struct PlayerPassedObstacle;
+
+fn track_obstacle_movement(
+    mut commands: Commands,
+    obstacles: Query<(Entity, &Transform), With<ObstacleMarker>>,
+    playfield: Res<Playfield>,
+    config: Res<GameConfig>,
+) {
+    let left_boundary = -(playfield.width / 2.0) - config.obstacle_width;
+
+    for (obstacle, transform) in obstacles.iter() {
+        if transform.translation.x < left_boundary {
+            commands.entity(obstacle).despawn_recursive();
+        }
+    }
+}
+
+pub fn fit_pipes_to_gap(
+    gaps: Query<&Gap, Changed<Gap>>,
+    mut sections: Query<(&PipeSection, &mut Transform)>,
+) {
+    for (section, mut transform) in sections.iter_mut() {
+        let Ok(gap) = gaps.get(section.obstacle) else {
+            continue;
+        };
+
+        transform.translation.y = match section.side {
+            PipeSide::Top => gap.top_pipe_offset(),
+            PipeSide::Bottom => gap.bottom_pipe_offset(),
+        };
+    }
+}
+
+pub type ObstacleNotScored = (With<ObstacleMarker>, Without<AlreadyScoredMarker>);
+
+pub fn score_obstacle(
+    mut commands: Commands,
+    obstacles: Query<(Entity, &Transform), ObstacleNotScored>,
+    mut passed_obstacle: EventWriter<PlayerPassedObstacle>,
+    config: Res<GameConfig>,
+) {
+    for (obstacle, transform) in obstacles.iter() {
+        if transform.translation.x < config.birb_x {
+            commands.entity(obstacle).insert(AlreadyScoredMarker);
+            passed_obstacle.send_default();
+        }
+    }
+}
+
+pub fn tick_spawn_timer(time: Res<Time>, mut obstacle_spawner: ResMut<ObstacleSpawnTimer>) {
+    obstacle_spawner.timer.tick(time.delta());
+}
+
+fn spawn_timer_finished(obstacle_spawner: Res<ObstacleSpawnTimer>) -> bool {
+    obstacle_spawner.timer.just_finished()
+}
+
+fn spawn_obstacle(
+    mut commands: Commands,
+    playfield: Res<Playfield>,
+    mut rng: GlobalEntropy<WyRand>,
+    mut generator: ResMut<PipeGenerator>,
+    assets: Res<SpriteAssets>,
+    pacing: CurrentPacing,
+) {
+    let config = &pacing.config;
+    let planned = generator.next(&mut **rng, config, &pacing.get());
+
+    spawn_pipe_pair(
+        &mut commands,
+        &assets,
+        config,
+        ObstacleSpec {
+            x: spawn_x(&playfield, config),
+            gap_center: planned.center,
+            gap_size: planned.size,
+            speed: planned.pipe_speed,
+            kind: planned.kind,
+        },
+    );
+}
+
+/// Where new obstacles appear, just past the right edge of the playfield.
+pub fn spawn_x(playfield: &Playfield, config: &GameConfig) -> f32 {
+    (playfield.width / 2.0) + config.obstacle_width
+}
+
+/// Everything that varies between pipe pairs.
+#[derive(Debug, Clone, Copy)]
+pub struct ObstacleSpec {
+    pub x: f32,
+    /// World-space height of the middle of the gap.
+    pub gap_center: f32,
+    pub gap_size: f32,
+    /// Leftward speed.
+    pub speed: f32,
+    pub kind: ObstacleKind,
+}
+
+/// The entities making up a freshly spawned pipe pair.
+#[derive(Debug, Clone, Copy)]
+pub struct SpawnedObstacle {
+    /// The physics body, carrying the [`Gap`].
+    pub body: Entity,
+    /// The interpolated parent of the pipe sprites, for anything else that
+    /// should be drawn moving along with them.
+    pub sprites: Entity,
+}
+
+pub fn spawn_pipe_pair(
+    commands: &mut Commands,
+    assets: &SpriteAssets,
+    config: &GameConfig,
+    spec: ObstacleSpec,
+) -> SpawnedObstacle {
+    let gap = Gap::with_size(spec.gap_size);
+    let transform = Transform::from_xyz(spec.x, spec.gap_center - gap.offset, 0.0);
+
+    let mut obstacle = commands.spawn((
+        Name::new("Obstacle"),
+        ObstacleMarker,
+        gap,
+        transform,
+        PhysicsHistory::at(transform.translation),
+        RigidBody::KinematicVelocityBased,
+        Velocity {
+            linvel: Vec2::new(-spec.speed, 0.0),
+            ..default()
+        },
+        Visibility::Visible,
+        StateScoped(AppState::InGame),
+    ));
+    spec.kind.insert(&mut obstacle, &gap);
+
+    let body = obstacle.id();
+    let top = PipeSection {
+        obstacle: body,
+        side: PipeSide::Top,
+    };
+    let bottom = PipeSection {
+        obstacle: body,
+        side: PipeSide::Bottom,
+    };
+    let mut sprites = None;
+
+    obstacle.with_children(|parent| {
+        parent.spawn((
+            Name::new("Top pipe"),
+            top,
+            Hazard::Pipe,
+            Collider::cuboid(config.obstacle_width, PIPE_HALF_HEIGHT),
+            Hazard::Pipe.collision_groups(),
+            Transform::from_xyz(0.0, gap.top_pipe_offset(), 0.0),
+            Sensor,
+        ));
+        parent.spawn((
+            Name::new("Bottom pipe"),
+            bottom,
+            Hazard::Pipe,
+            Collider::cuboid(config.obstacle_width, PIPE_HALF_HEIGHT),
+            Hazard::Pipe.collision_groups(),
+            Transform::from_xyz(0.0, gap.bottom_pipe_offset(), 0.0),
+            Sensor,
+        ));
+
+        let sprite_parent = parent
+            .spawn((
+                Name::new("Pipe sprites"),
+                Transform::default(),
+                Visibility::Inherited,
+                InterpolatedVisual,
+            ))
+            .with_children(|sprites| {
+                let mut flipped_sprite = Sprite::from_image(assets.green_pipe.clone());
+                flipped_sprite.flip_y = true;
+
+                sprites.spawn((
+                    Name::new("Top pipe sprite"),
+                    top,
+                    flipped_sprite,
+                    Transform::from_xyz(0.0, gap.top_pipe_offset(), 0.0),
+                ));
+                sprites.spawn((
+                    Name::new("Bottom pipe sprite"),
+                    bottom,
+                    Sprite::from_image(assets.green_pipe.clone()),
+                    Transform::from_xyz(0.0, gap.bottom_pipe_offset(), 0.0),
+                ));
+            })
+            .id();
+        sprites = Some(sprite_parent);
+    });
+
+    SpawnedObstacle {
+        body,
+        sprites: sprites.expect("pipe sprites are spawned with the obstacle"),
+    }
+}
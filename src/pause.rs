@@ -0,0 +1,137 @@
+use crate::*;
+use bevy::window::WindowFocused;
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, application: &mut App) {
+        application
+            .configure_sets(Update, input::LiveInputSet.run_if(not(paused_in_game)))
+            .add_systems(OnEnter(GameState::Paused), spawn_pause_menu)
+            .add_systems(
+                Update,
+                (
+                    toggle_pause,
+                    pause_on_focus_lost.run_if(resource_exists::<Events<WindowFocused>>),
+                    handle_pause_menu.run_if(in_state(GameState::Paused)),
+                )
+                    .run_if(in_state(AppState::InGame)),
+            );
+    }
+}
+
+#[derive(Debug, Component)]
+pub struct PauseMenuMarker;
+
+#[derive(Debug, Clone, Copy, Component)]
+pub enum PauseButton {
+    Resume,
+    Restart,
+    QuitToMenu,
+}
+
+impl PauseButton {
+    fn label(&self) -> &'static str {
+        match self {
+            PauseButton::Resume => "Resume",
+            PauseButton::Restart => "Restart",
+            PauseButton::QuitToMenu => "Quit to Menu",
+        }
+    }
+}
+
+fn paused_in_game(app_state: Res<State<AppState>>, game_state: Res<State<GameState>>) -> bool {
+    *app_state.get() == AppState::InGame && *game_state.get() == GameState::Paused
+}
+
+fn toggle_pause(
+    keys: Res<ButtonInput<KeyCode>>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !keys.any_just_pressed([KeyCode::Escape, KeyCode::KeyP]) {
+        return;
+    }
+
+    match state.get() {
+        GameState::Running => next_state.set(GameState::Paused),
+        GameState::Paused => next_state.set(GameState::Running),
+        GameState::Dying | GameState::GameOver => {}
+    }
+}
+
+fn pause_on_focus_lost(
+    mut focus_events: EventReader<WindowFocused>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let lost_focus = focus_events.read().any(|event| !event.focused);
+
+    if lost_focus && *state.get() == GameState::Running {
+        next_state.set(GameState::Paused);
+    }
+}
+
+fn spawn_pause_menu(mut commands: Commands) {
+    commands
+        .spawn((
+            Name::new("Pause menu"),
+            PauseMenuMarker,
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(15.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+            StateScoped(GameState::Paused),
+            StateScoped(AppState::InGame),
+        ))
+        .with_children(|parent| {
+            parent.spawn((Text::new("Paused"), TextFont::from_font_size(48.0)));
+
+            for button in [
+                PauseButton::Resume,
+                PauseButton::Restart,
+                PauseButton::QuitToMenu,
+            ] {
+                parent
+                    .spawn((
+                        Name::new(button.label()),
+                        button,
+                        Button,
+                        Node {
+                            width: Val::Px(240.0),
+                            padding: UiRect::all(Val::Px(10.0)),
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
+                    ))
+                    .with_children(|button_parent| {
+                        button_parent.spawn(Text::new(button.label()));
+                    });
+            }
+        });
+}
+
+fn handle_pause_menu(
+    buttons: Query<(&Interaction, &PauseButton), Changed<Interaction>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+) {
+    for (interaction, button) in buttons.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match button {
+            PauseButton::Resume => next_game_state.set(GameState::Running),
+            PauseButton::Restart => next_app_state.set(AppState::Restarting),
+            PauseButton::QuitToMenu => next_app_state.set(AppState::Menu),
+        }
+    }
+}
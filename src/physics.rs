@@ -0,0 +1,106 @@
+use crate::*;
+use bevy::transform::TransformSystem;
+use bevy_rapier2d::prelude::*;
+
+/// World units, which are screen pixels at the default window size, per
+/// Rapier metre.
+pub const PIXELS_PER_METER: f32 = 100.0;
+
+pub struct PhysicsPlugin;
+
+impl Plugin for PhysicsPlugin {
+    fn build(&self, application: &mut App) {
+        application
+            .insert_resource(Time::<Fixed>::from_hz(TICK_RATE_HZ))
+            .add_plugins(
+                RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(PIXELS_PER_METER)
+                    .in_fixed_schedule(),
+            )
+            .add_systems(Startup, match_timestep_to_fixed_time)
+            .add_systems(OnEnter(GameState::Running), start_physics)
+            .add_systems(OnEnter(GameState::GameOver), stop_physics)
+            .add_systems(OnEnter(GameState::Paused), stop_physics)
+            .add_systems(FixedLast, record_physics_history)
+            .add_systems(
+                PostUpdate,
+                interpolate_visuals.before(TransformSystem::TransformPropagate),
+            );
+
+        #[cfg(feature = "debug")]
+        application.add_plugins(RapierDebugRenderPlugin::default());
+    }
+}
+
+/// The last two fixed-step positions of a physics body.
+///
+/// Bodies only move on fixed ticks, so anything drawn directly from their
+/// transform stutters whenever the frame rate doesn't line up with the tick
+/// rate. Children marked with [`InterpolatedVisual`] are drawn between these.
+#[derive(Debug, Clone, Copy, Component)]
+pub struct PhysicsHistory {
+    pub previous: Vec2,
+    pub current: Vec2,
+}
+
+impl PhysicsHistory {
+    pub fn at(translation: Vec3) -> Self {
+        Self {
+            previous: translation.truncate(),
+            current: translation.truncate(),
+        }
+    }
+}
+
+/// A child whose transform is offset so it renders at the parent's
+/// interpolated position instead of its latest simulated one.
+///
+/// Keep colliders off these entities; only sprites should move with them.
+#[derive(Debug, Default, Clone, Copy, Component)]
+pub struct InterpolatedVisual;
+
+fn match_timestep_to_fixed_time(time: Res<Time<Fixed>>, mut timestep: ResMut<TimestepMode>) {
+    *timestep = TimestepMode::Fixed {
+        dt: time.timestep().as_secs_f32(),
+        substeps: 1,
+    };
+}
+
+pub fn start_physics(mut physics_config: Query<&mut RapierConfiguration>) {
+    for mut config in physics_config.iter_mut() {
+        config.physics_pipeline_active = true;
+    }
+}
+
+pub fn stop_physics(mut physics_config: Query<&mut RapierConfiguration>) {
+    for mut config in physics_config.iter_mut() {
+        config.physics_pipeline_active = false;
+    }
+}
+
+fn record_physics_history(mut bodies: Query<(&Transform, &mut PhysicsHistory)>) {
+    for (transform, mut history) in bodies.iter_mut() {
+        history.previous = history.current;
+        history.current = transform.translation.truncate();
+    }
+}
+
+fn interpolate_visuals(
+    time: Res<Time<Fixed>>,
+    bodies: Query<(&PhysicsHistory, &Transform), Without<InterpolatedVisual>>,
+    mut visuals: Query<(&Parent, &mut Transform), With<InterpolatedVisual>>,
+) {
+    let alpha = time.overstep_fraction();
+
+    for (parent, mut transform) in visuals.iter_mut() {
+        let Ok((history, body)) = bodies.get(parent.get()) else {
+            continue;
+        };
+
+        // The offset is in world space, but the child's transform is scaled
+        // along with its parent's.
+        let offset = (history.previous.lerp(history.current, alpha) - history.current)
+            / body.scale.truncate();
+        transform.translation.x = offset.x;
+        transform.translation.y = offset.y;
+    }
+}
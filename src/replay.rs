@@ -0,0 +1,388 @@
+use crate::{
+    config::{GameConfig, GameConfigError, PinnedConfig},
+    difficulty::Difficulty,
+    game::{Playfield, RunSeed, RunTick, Score},
+    input::{ButtonPressed, LiveInputSet},
+    multiplayer::single_player,
+    *,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+/// Replays of any other version are refused rather than re-simulated under
+/// rules they weren't recorded with.
+pub const REPLAY_VERSION: u32 = 4;
+
+/// Records a run to disk, or plays a previously recorded one back.
+pub enum ReplayPlugin {
+    /// Write every run's inputs to the given file when the bird dies.
+    Record(PathBuf),
+    /// Feed the inputs from a replay into the game instead of the player's.
+    Playback(Box<Replay>),
+}
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, application: &mut App) {
+        match self {
+            ReplayPlugin::Record(path) => {
+                application
+                    .insert_resource(RecordingPath(path.clone()))
+                    .init_resource::<Recording>()
+                    .add_systems(
+                        OnEnter(AppState::InGame),
+                        start_recording.after(obstacles::seed_rng),
+                    )
+                    .add_systems(
+                        FixedUpdate,
+                        record_input
+                            .run_if(in_state(AppState::InGame))
+                            .run_if(in_state(GameState::Running)),
+                    )
+                    .add_systems(
+                        OnEnter(GameState::GameOver),
+                        // Replays only hold one player's presses.
+                        save_recording.run_if(single_player),
+                    );
+            }
+            ReplayPlugin::Playback(replay) => {
+                application
+                    .insert_resource(replay.config.game.clone())
+                    .init_resource::<PinnedConfig>()
+                    .insert_resource(replay.config.playfield())
+                    .insert_resource(replay.config.difficulty)
+                    .insert_resource(Time::<Fixed>::from_hz(replay.config.timestep_hz))
+                    .insert_resource(Playback {
+                        replay: Replay::clone(replay),
+                        next_input: 0,
+                    })
+                    .configure_sets(Update, LiveInputSet.run_if(not(in_state(AppState::InGame))))
+                    .add_systems(OnEnter(AppState::Menu), restart_playback)
+                    .add_systems(OnEnter(AppState::Restarting), restart_playback)
+                    .add_systems(
+                        FixedFirst,
+                        play_input
+                            .run_if(in_state(AppState::InGame))
+                            .run_if(in_state(GameState::Running)),
+                    )
+                    .add_systems(OnEnter(GameState::GameOver), report_playback);
+            }
+        }
+    }
+}
+
+/// A recorded run: everything needed to simulate it again from scratch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub version: u32,
+    pub seed: u64,
+    pub config: ReplayConfig,
+    /// [`ReplayConfig::hash`] of `config`, so a replay damaged in transit or
+    /// edited by hand stands out. Whoever wrote the replay could have
+    /// written a matching hash, so this is an integrity check, not proof
+    /// the config is the one the game really ran on.
+    pub config_hash: u64,
+    /// The fixed tick of every button press, in order.
+    pub inputs: Vec<u64>,
+    /// The score the run claims to have finished with.
+    pub score: usize,
+}
+
+/// Simulation settings in effect while a replay was recorded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayConfig {
+    pub timestep_hz: f64,
+    pub playfield_width: f32,
+    pub playfield_height: f32,
+    pub difficulty: Difficulty,
+    pub game: GameConfig,
+}
+
+impl ReplayConfig {
+    /// The settings the game itself runs on, with `game` and `difficulty`
+    /// being the only ones a player can choose.
+    pub fn official(game: GameConfig, difficulty: Difficulty) -> Self {
+        let playfield = Playfield::default();
+
+        Self {
+            timestep_hz: TICK_RATE_HZ,
+            playfield_width: playfield.width,
+            playfield_height: playfield.height,
+            difficulty,
+            game,
+        }
+    }
+
+    pub fn playfield(&self) -> Playfield {
+        Playfield {
+            width: self.playfield_width,
+            height: self.playfield_height,
+        }
+    }
+
+    /// A fingerprint of every setting, stable across builds and platforms.
+    ///
+    /// This is 64-bit FNV-1a over the config's RON form, rather than `Hash`,
+    /// whose output isn't guaranteed to stay the same between Rust releases.
+    pub fn hash(&self) -> u64 {
+        const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0100_0000_01b3;
+
+        let contents = ron::ser::to_string(self).unwrap_or_default();
+
+        contents.bytes().fold(OFFSET_BASIS, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+        })
+    }
+}
+
+impl Replay {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ReplayError> {
+        let path = path.as_ref();
+        let contents =
+            fs::read_to_string(path).map_err(|error| ReplayError::Io(path.to_path_buf(), error))?;
+        let replay: Replay = ron::from_str(&contents).map_err(ReplayError::Parse)?;
+
+        if replay.version != REPLAY_VERSION {
+            return Err(ReplayError::UnsupportedVersion(replay.version));
+        }
+
+        replay.validate()?;
+
+        Ok(replay)
+    }
+
+    /// Checks the settings are ones the game can be run on at all, since a
+    /// replay file can say anything.
+    pub fn validate(&self) -> Result<(), ReplayError> {
+        let config = &self.config;
+
+        // The game only ever ticks at one rate, and nothing is tuned to hold
+        // up at any other.
+        if config.timestep_hz != TICK_RATE_HZ {
+            return Err(ReplayError::Invalid(format!(
+                "timestep_hz must be {TICK_RATE_HZ}, got {}",
+                config.timestep_hz
+            )));
+        }
+
+        for (field, value) in [
+            ("playfield_width", config.playfield_width),
+            ("playfield_height", config.playfield_height),
+        ] {
+            if !value.is_finite() || value <= 0.0 {
+                return Err(ReplayError::Invalid(format!(
+                    "{field} must be greater than zero, got {value}"
+                )));
+            }
+        }
+
+        config.game.validate().map_err(ReplayError::Config)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ReplayError> {
+        let path = path.as_ref();
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(ReplayError::Serialize)?;
+
+        fs::write(path, contents).map_err(|error| ReplayError::Io(path.to_path_buf(), error))
+    }
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(PathBuf, std::io::Error),
+    Parse(ron::error::SpannedError),
+    Serialize(ron::Error),
+    UnsupportedVersion(u32),
+    Invalid(String),
+    Config(GameConfigError),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(path, error) => write!(f, "{}: {error}", path.display()),
+            ReplayError::Parse(error) => write!(f, "invalid replay file: {error}"),
+            ReplayError::Serialize(error) => write!(f, "could not write replay: {error}"),
+            ReplayError::UnsupportedVersion(version) => write!(
+                f,
+                "replay version {version} is not supported (expected {REPLAY_VERSION})"
+            ),
+            ReplayError::Invalid(reason) => write!(f, "invalid replay: {reason}"),
+            ReplayError::Config(error) => write!(f, "replay was recorded on an {error}"),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+#[derive(Resource)]
+struct RecordingPath(PathBuf);
+
+#[derive(Default, Resource)]
+struct Recording {
+    seed: u64,
+    inputs: Vec<u64>,
+}
+
+fn start_recording(mut recording: ResMut<Recording>, seed: Res<RunSeed>) {
+    recording.seed = seed.0;
+    recording.inputs.clear();
+}
+
+fn record_input(
+    mut input_pressed: EventReader<ButtonPressed>,
+    mut recording: ResMut<Recording>,
+    tick: Res<RunTick>,
+) {
+    for _ in input_pressed.read() {
+        recording.inputs.push(tick.0);
+    }
+}
+
+fn save_recording(
+    recording: Res<Recording>,
+    path: Res<RecordingPath>,
+    score: Res<Score>,
+    playfield: Res<Playfield>,
+    time: Res<Time<Fixed>>,
+    config: Res<GameConfig>,
+    difficulty: Res<Difficulty>,
+) {
+    let replay_config = ReplayConfig {
+        timestep_hz: 1.0 / time.timestep().as_secs_f64(),
+        playfield_width: playfield.width,
+        playfield_height: playfield.height,
+        difficulty: *difficulty,
+        game: config.clone(),
+    };
+    let replay = Replay {
+        version: REPLAY_VERSION,
+        seed: recording.seed,
+        config_hash: replay_config.hash(),
+        config: replay_config,
+        inputs: recording.inputs.clone(),
+        score: score.value(),
+    };
+
+    match replay.save(&path.0) {
+        Ok(()) => info!("Saved replay to {}", path.0.display()),
+        Err(error) => error!("Failed to save replay: {error}"),
+    }
+}
+
+#[derive(Resource)]
+struct Playback {
+    replay: Replay,
+    next_input: usize,
+}
+
+fn restart_playback(mut playback: ResMut<Playback>, mut seed: ResMut<RunSeed>) {
+    playback.next_input = 0;
+    seed.0 = playback.replay.seed;
+}
+
+fn play_input(
+    mut playback: ResMut<Playback>,
+    mut event_pressed: EventWriter<ButtonPressed>,
+    tick: Res<RunTick>,
+) {
+    while let Some(&input_tick) = playback.replay.inputs.get(playback.next_input) {
+        if input_tick > tick.0 {
+            break;
+        }
+
+        event_pressed.send_default();
+        playback.next_input += 1;
+    }
+}
+
+fn report_playback(playback: Res<Playback>, score: Res<Score>) {
+    let claimed = playback.replay.score;
+    let actual = score.value();
+
+    if claimed == actual {
+        info!("Replay finished with the recorded score of {actual}");
+    } else {
+        warn!("Replay finished with score {actual}, but {claimed} was recorded");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headless::tests::{game_state, headless_app, in_game, run_to_game_over};
+
+    #[test]
+    fn playing_a_recording_back_reproduces_the_run() {
+        let path =
+            std::env::temp_dir().join(format!("birb-replay-round-trip-{}.ron", std::process::id()));
+
+        let mut recorder = headless_app(11, |application| {
+            application.add_plugins(ReplayPlugin::Record(path.clone()));
+        });
+        let mut last_flap = None;
+        let recorded = run_to_game_over(&mut recorder, |application| {
+            let tick = application.world().resource::<RunTick>().0;
+            let running =
+                in_game(application) && game_state(application) == Some(GameState::Running);
+
+            // A flap every half second keeps the bird up for a while.
+            if running && tick % 32 == 10 && last_flap != Some(tick) {
+                application.world_mut().send_event(ButtonPressed::default());
+                last_flap = Some(tick);
+            }
+        });
+
+        let replay = Replay::load(&path).expect("the run should have been saved");
+        let _ = fs::remove_file(&path);
+        assert!(!replay.inputs.is_empty());
+        assert_eq!(replay.score, recorded.1);
+
+        let mut player = headless_app(replay.seed, |application| {
+            application.add_plugins(ReplayPlugin::Playback(Box::new(replay.clone())));
+        });
+        let replayed = run_to_game_over(&mut player, |_| {});
+
+        assert_eq!(replayed, recorded);
+    }
+
+    #[test]
+    fn replays_with_unplayable_settings_are_rejected() {
+        let replay = Replay {
+            version: REPLAY_VERSION,
+            seed: 0,
+            config: ReplayConfig {
+                timestep_hz: TICK_RATE_HZ,
+                playfield_width: 400.0,
+                playfield_height: 600.0,
+                difficulty: Difficulty::Normal,
+                game: GameConfig::default(),
+            },
+            config_hash: 0,
+            inputs: Vec::new(),
+            score: 0,
+        };
+        assert!(replay.validate().is_ok());
+
+        for timestep_hz in [0.0, -64.0, 5.0, 128.0, f64::NAN, f64::INFINITY, 1e12] {
+            let mut bad = replay.clone();
+            bad.config.timestep_hz = timestep_hz;
+            assert!(
+                matches!(bad.validate(), Err(ReplayError::Invalid(_))),
+                "timestep_hz {timestep_hz}"
+            );
+        }
+
+        let mut bad = replay.clone();
+        bad.config.playfield_height = f32::NAN;
+        assert!(matches!(bad.validate(), Err(ReplayError::Invalid(_))));
+
+        let mut bad = replay;
+        bad.config.game.pipe_speed = -1.0;
+        assert!(matches!(bad.validate(), Err(ReplayError::Config(_))));
+    }
+}
@@ -0,0 +1,234 @@
+//! The results panel shown when a solo run ends.
+//!
+//! It sums the run up: the final score against the personal best, the medal
+//! earned for the obstacles passed, and how long the bird stayed up. Levels
+//! and shared rounds show their own results instead.
+
+use crate::{
+    autopilot::Autopilot,
+    daily::{DailyBests, DailyChallenge},
+    difficulty::Difficulty,
+    game::{GameMode, GameOverLockout, RunTick, Score},
+    highscores::HighScores,
+    levels::ActiveLevel,
+    multiplayer::single_player,
+    *,
+};
+use std::fmt;
+
+pub struct ResultsPlugin;
+
+impl Plugin for ResultsPlugin {
+    fn build(&self, application: &mut App) {
+        application
+            .init_resource::<RunRecord>()
+            .add_systems(OnEnter(AppState::InGame), remember_record)
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                spawn_results_panel
+                    .run_if(not(resource_exists::<ActiveLevel>))
+                    .run_if(single_player),
+            )
+            .add_systems(
+                Update,
+                show_continue_hint
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(in_state(GameState::GameOver)),
+            );
+    }
+}
+
+/// Awarded for the number of obstacles a run gets past.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Medal {
+    Bronze,
+    Silver,
+    Gold,
+    Platinum,
+}
+
+impl Medal {
+    /// Obstacles a run must pass for each medal at `difficulty`, lowest
+    /// first.
+    pub fn thresholds(difficulty: Difficulty) -> [(Medal, usize); 4] {
+        let [bronze, silver, gold, platinum] = match difficulty {
+            Difficulty::Easy => [15, 30, 45, 60],
+            Difficulty::Normal => [10, 20, 30, 40],
+            Difficulty::Hard => [5, 15, 25, 35],
+        };
+
+        [
+            (Medal::Bronze, bronze),
+            (Medal::Silver, silver),
+            (Medal::Gold, gold),
+            (Medal::Platinum, platinum),
+        ]
+    }
+
+    /// The best medal for passing `obstacles` at `difficulty`, if it earns
+    /// one at all.
+    pub fn for_obstacles(obstacles: usize, difficulty: Difficulty) -> Option<Medal> {
+        Self::thresholds(difficulty)
+            .iter()
+            .rev()
+            .find(|(_, threshold)| obstacles >= *threshold)
+            .map(|(medal, _)| *medal)
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            Medal::Bronze => Color::srgb(0.8, 0.5, 0.2),
+            Medal::Silver => Color::srgb(0.75, 0.75, 0.8),
+            Medal::Gold => Color::srgb(1.0, 0.8, 0.1),
+            Medal::Platinum => Color::srgb(0.7, 0.95, 1.0),
+        }
+    }
+}
+
+impl fmt::Display for Medal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Medal::Bronze => write!(f, "Bronze"),
+            Medal::Silver => write!(f, "Silver"),
+            Medal::Gold => write!(f, "Gold"),
+            Medal::Platinum => write!(f, "Platinum"),
+        }
+    }
+}
+
+/// The record the current run counts towards, as it stood when the run
+/// started, so the results can tell whether it was beaten no matter when
+/// the records themselves get updated.
+#[derive(Debug, Default, Resource)]
+struct RunRecord {
+    /// What the run was, like "Endless on Hard" or "Daily 2025-01-31".
+    heading: String,
+    /// The best score before this run, or `None` if no records are kept
+    /// for it.
+    best: Option<usize>,
+}
+
+fn remember_record(
+    mut record: ResMut<RunRecord>,
+    mode: Res<GameMode>,
+    daily: Res<DailyChallenge>,
+    difficulty: Res<Difficulty>,
+    high_scores: Option<Res<HighScores>>,
+    daily_bests: Option<Res<DailyBests>>,
+) {
+    *record = match *mode {
+        GameMode::Daily => RunRecord {
+            heading: format!("Daily {}", daily.date),
+            best: daily_bests.map(|bests| bests.get(daily.date).unwrap_or_default()),
+        },
+        other => RunRecord {
+            heading: format!("{other} on {}", *difficulty),
+            best: high_scores.map(|high_scores| {
+                high_scores
+                    .best(other, *difficulty)
+                    .map_or(0, |best| best.score)
+            }),
+        },
+    };
+}
+
+#[derive(Debug, Component)]
+pub struct ContinueHintMarker;
+
+fn spawn_results_panel(
+    mut commands: Commands,
+    score: Res<Score>,
+    tick: Res<RunTick>,
+    time: Res<Time<Fixed>>,
+    record: Res<RunRecord>,
+    difficulty: Res<Difficulty>,
+    autopilot: Option<Res<Autopilot>>,
+) {
+    // Mirrors the record keepers: bot runs and empty runs don't count.
+    let flown_by_bot = autopilot.is_some_and(|autopilot| autopilot.enabled);
+    let counts = score.value() > 0 && !flown_by_bot;
+    let new_best = counts && record.best.is_some_and(|best| score.value() > best);
+
+    let seconds = tick.0 as f64 * time.timestep().as_secs_f64();
+    let medal = Medal::for_obstacles(score.obstacles_passed(), *difficulty);
+
+    let mut lines = vec![
+        (record.heading.clone(), 22.0, Color::WHITE),
+        (format!("Score {}", score.value()), 40.0, Color::WHITE),
+    ];
+
+    if let Some(best) = record.best {
+        let best = if new_best { score.value() } else { best };
+        lines.push((format!("Best {best}"), 24.0, Color::WHITE));
+    }
+    if new_best {
+        lines.push(("New best!".to_string(), 28.0, Color::srgb(1.0, 0.85, 0.2)));
+    }
+
+    lines.push(match medal {
+        Some(medal) => (format!("{medal} medal"), 28.0, medal.color()),
+        None => ("No medal".to_string(), 20.0, Color::srgb(0.7, 0.7, 0.7)),
+    });
+
+    lines.push((
+        format!("{} pipes passed in {seconds:.1}s", score.obstacles_passed()),
+        20.0,
+        Color::WHITE,
+    ));
+
+    commands
+        .spawn((
+            Name::new("Results panel"),
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(430.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            StateScoped(AppState::InGame),
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    Node {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        row_gap: Val::Px(6.0),
+                        padding: UiRect::axes(Val::Px(30.0), Val::Px(20.0)),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+                ))
+                .with_children(|panel| {
+                    for (text, size, color) in lines {
+                        panel.spawn((
+                            Text::new(text),
+                            TextFont::from_font_size(size),
+                            TextColor(color),
+                        ));
+                    }
+
+                    panel.spawn((
+                        ContinueHintMarker,
+                        Text::new("Flap for the menu"),
+                        TextFont::from_font_size(18.0),
+                        Visibility::Hidden,
+                    ));
+                });
+        });
+}
+
+/// Reveals the continue hint once the game over screen starts taking input.
+fn show_continue_hint(
+    lockout: Res<GameOverLockout>,
+    mut hints: Query<&mut Visibility, With<ContinueHintMarker>>,
+) {
+    if !lockout.0.finished() {
+        return;
+    }
+
+    for mut visibility in hints.iter_mut() {
+        *visibility = Visibility::Inherited;
+    }
+}
@@ -0,0 +1,110 @@
+//! Small key/value persistence for save files.
+//!
+//! Native builds keep one file per key in the platform's data directory; the
+//! web build has no file system, so it uses the browser's `localStorage`.
+//!
+//! Saves are RON documents with a top-level `version`, which is bumped
+//! whenever the layout changes in a way older builds can't read. Loading
+//! ignores saves of any other version rather than misreading them.
+
+use bevy::log::{error, warn};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+#[cfg(not(target_arch = "wasm32"))]
+use std::{fs, path::PathBuf};
+
+/// The part of a save every version agrees on.
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+}
+
+/// Reads the save under `key`, or `None` if there isn't one of `version`
+/// that parses.
+pub fn load_ron<T: DeserializeOwned>(key: &str, version: u32) -> Option<T> {
+    let contents = load(key)?;
+
+    let result = ron::from_str::<Header>(&contents).and_then(|header| {
+        if header.version != version {
+            warn!(
+                "Ignoring {key} saved with unsupported version {}",
+                header.version
+            );
+            return Ok(None);
+        }
+
+        ron::from_str::<T>(&contents).map(Some)
+    });
+
+    result.unwrap_or_else(|error| {
+        warn!("Ignoring unreadable {key}: {error}");
+        None
+    })
+}
+
+/// Writes `value` under `key`, logging rather than failing if it can't.
+pub fn save_ron<T: Serialize>(key: &str, value: &T) {
+    let result = ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())
+        .map_err(|error| error.to_string())
+        .and_then(|contents| save(key, &contents));
+
+    if let Err(error) = result {
+        error!("Failed to save {key}: {error}");
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load(key: &str) -> Option<String> {
+    fs::read_to_string(path_for(key)?).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save(key: &str, contents: &str) -> Result<(), String> {
+    let path = path_for(key).ok_or("no data directory available")?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|error| format!("{}: {error}", parent.display()))?;
+    }
+
+    fs::write(&path, contents).map_err(|error| format!("{}: {error}", path.display()))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn path_for(key: &str) -> Option<PathBuf> {
+    Some(data_dir()?.join("birb").join(format!("{key}.ron")))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn data_dir() -> Option<PathBuf> {
+    let env_path = |name: &str| std::env::var_os(name).map(PathBuf::from);
+
+    if cfg!(target_os = "windows") {
+        env_path("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        env_path("HOME").map(|home| home.join("Library/Application Support"))
+    } else {
+        env_path("XDG_DATA_HOME").or_else(|| env_path("HOME").map(|home| home.join(".local/share")))
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn load(key: &str) -> Option<String> {
+    local_storage()?.get_item(&storage_key(key)).ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn save(key: &str, contents: &str) -> Result<(), String> {
+    local_storage()
+        .ok_or("localStorage is not available")?
+        .set_item(&storage_key(key), contents)
+        .map_err(|error| format!("{error:?}"))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+fn storage_key(key: &str) -> String {
+    format!("birb.{key}")
+}
@@ -0,0 +1,155 @@
+//! Checks a replay's claimed score by playing it back without a window.
+//!
+//! The replay's inputs are fed into a fresh headless game on the replay's
+//! seed and settings, one fixed tick per update, and whatever score the bird
+//! actually finishes with is compared against the claim, so the score
+//! itself is never taken on trust.
+//!
+//! The settings are another matter: the replay carries them along with a
+//! hash, but whoever wrote the replay could have written both. The hash only
+//! catches a replay that was damaged or carelessly edited. To hold a replay
+//! to known settings, such as a leaderboard's, pass in the
+//! [`ReplayConfig::official`] ones.
+
+use crate::{
+    BirbPlugins,
+    daily::DailyBestsPlugin,
+    game::{RunTick, Score},
+    ghost::GhostPlugin,
+    headless::HeadlessPlugin,
+    highscores::HighScoresPlugin,
+    levels::LevelRecordsPlugin,
+    replay::{Replay, ReplayConfig, ReplayPlugin},
+    *,
+};
+use bevy::time::TimeUpdateStrategy;
+use std::{fmt, time::Duration};
+
+/// How long a run may go on after its last input before giving up on it.
+const GRACE_SECONDS: f64 = 60.0;
+/// Updates allowed for getting from startup into the run.
+const MAX_STARTUP_UPDATES: u64 = 100;
+
+/// What re-simulating a replay found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Verification {
+    pub claimed_score: usize,
+    pub score: usize,
+    /// The fixed tick the bird died on, or `None` if it was still flying
+    /// when the simulation gave up.
+    pub death_tick: Option<u64>,
+    pub timestep_hz: u64,
+    /// Whether the replay's config still matches the hash recorded
+    /// alongside it. This catches damage, not deliberate tampering.
+    pub config_intact: bool,
+    /// Whether the replay was played on exactly the official settings, when
+    /// they were given.
+    pub config_expected: Option<bool>,
+}
+
+impl Verification {
+    /// Whether the claimed score stands.
+    pub fn is_valid(&self) -> bool {
+        self.claimed_score == self.score
+            && self.death_tick.is_some()
+            && self.config_intact
+            && self.config_expected != Some(false)
+    }
+}
+
+impl fmt::Display for Verification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let check = |ok: bool| if ok { "ok" } else { "MISMATCH" };
+
+        writeln!(f, "Config integrity: {}", check(self.config_intact))?;
+        if let Some(expected) = self.config_expected {
+            writeln!(f, "Official settings: {}", check(expected))?;
+        }
+        writeln!(
+            f,
+            "Score: claimed {}, simulated {} ({})",
+            self.claimed_score,
+            self.score,
+            check(self.claimed_score == self.score)
+        )?;
+
+        match self.death_tick {
+            Some(tick) => writeln!(
+                f,
+                "Died at tick {tick} ({:.2}s)",
+                tick as f64 / self.timestep_hz as f64
+            )?,
+            None => writeln!(f, "Still flying when the simulation gave up")?,
+        }
+
+        write!(
+            f,
+            "Verdict: {}",
+            if self.is_valid() { "VALID" } else { "INVALID" }
+        )
+    }
+}
+
+/// Plays `replay` back headlessly and reports how it really ended.
+///
+/// With `official` settings, the replay must also have been played on
+/// exactly those, tick rate and playfield included, as a leaderboard would
+/// require.
+pub fn verify(replay: &Replay, official: Option<&ReplayConfig>) -> Verification {
+    let config_expected = official.map(|official| official.hash() == replay.config.hash());
+
+    let mut application = App::new();
+    application
+        .add_plugins(HeadlessPlugin {
+            logging: false,
+            exit_on_game_over: false,
+        })
+        .add_plugins(
+            BirbPlugins { seed: replay.seed }
+                .build()
+                .disable::<HighScoresPlugin>()
+                .disable::<LevelRecordsPlugin>()
+                .disable::<DailyBestsPlugin>()
+                .disable::<GhostPlugin>(),
+        )
+        .add_plugins(ReplayPlugin::Playback(Box::new(replay.clone())))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            1.0 / replay.config.timestep_hz,
+        )));
+    application.finish();
+    application.cleanup();
+
+    let last_input = replay.inputs.last().copied().unwrap_or_default();
+    let max_ticks = last_input.saturating_add((GRACE_SECONDS * replay.config.timestep_hz) as u64);
+    let mut death_tick = None;
+
+    // Time advances by exactly the replay's timestep per update, so once the
+    // run has started every update is one tick.
+    for _ in 0..max_ticks.saturating_add(MAX_STARTUP_UPDATES) {
+        application.update();
+
+        let world = application.world();
+        let tick = world.resource::<RunTick>().0;
+        let game_state = world.get_resource::<State<GameState>>().map(State::get);
+
+        // The tick stops advancing at the hit, so there's no need to wait
+        // for the bird to land.
+        if matches!(game_state, Some(GameState::Dying | GameState::GameOver)) {
+            death_tick = Some(tick);
+            break;
+        }
+
+        if tick > max_ticks {
+            break;
+        }
+    }
+
+    Verification {
+        claimed_score: replay.score,
+        score: application.world().resource::<Score>().value(),
+        death_tick,
+        timestep_hz: replay.config.timestep_hz.round() as u64,
+        config_intact: replay.config_hash == replay.config.hash(),
+        config_expected,
+    }
+}
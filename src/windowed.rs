@@ -0,0 +1,54 @@
+use crate::{config::ConfigAssets, levels::LevelAssets, *};
+use bevy::window::WindowTheme;
+
+/// Runs the game in a real window with rendering, audio and asset loading.
+pub struct WindowedPlugin;
+
+impl Plugin for WindowedPlugin {
+    fn build(&self, application: &mut App) {
+        let default_plugins = DefaultPlugins
+            .set(WindowPlugin {
+                primary_window: Some(Window {
+                    title: "Blappy birb".into(),
+                    name: Some("blappy_birb.app".into()),
+                    window_theme: Some(WindowTheme::Dark),
+                    // This breaks on WSL for some reason
+                    #[cfg(target_arch = "wasm32")]
+                    canvas: Some("#birb_canvas".into()),
+                    resolution: bevy::window::WindowResolution::new(WINDOW_WIDTH, WINDOW_HEIGHT),
+                    ..default()
+                }),
+                ..default()
+            })
+            .set(AssetPlugin {
+                meta_check: AssetMetaCheck::Never,
+                // Lets designers tweak the game config while it's running.
+                watch_for_changes_override: Some(cfg!(not(target_arch = "wasm32"))),
+                ..default()
+            });
+
+        application
+            .add_plugins(default_plugins)
+            .init_state::<AppState>()
+            .add_loading_state(
+                LoadingState::new(AppState::Loading)
+                    .continue_to_state(AppState::Menu)
+                    .load_collection::<AudioAssets>()
+                    .load_collection::<SpriteAssets>()
+                    .load_collection::<ConfigAssets>()
+                    .load_collection::<LevelAssets>(),
+            )
+            .add_systems(Startup, setup_camera)
+            .add_systems(Update, escape_to_quit.run_if(in_state(AppState::Menu)));
+    }
+}
+
+fn setup_camera(mut commands: Commands) {
+    commands.spawn(Camera2d);
+}
+
+fn escape_to_quit(keys: Res<ButtonInput<KeyCode>>, mut app_exit: EventWriter<AppExit>) {
+    if keys.just_pressed(KeyCode::Escape) {
+        app_exit.send_default();
+    }
+}